@@ -123,6 +123,42 @@ fn bench(c: &mut Criterion) {
         })
     });
 
+    let reader = maxminddb::ReaderBuilder::new()
+        .jump_table(16)
+        .open_file(path)
+        .unwrap();
+    group.bench_function("in-memory-jump-table", |b| {
+        b.iter(|| {
+            for ip in ips.iter() {
+                let _ = reader.lookup::<maxminddb::City>(*ip);
+            }
+        })
+    });
+
+    let reader = maxminddb::ReaderBuilder::new()
+        .blocked_tree(true)
+        .open_file(path)
+        .unwrap();
+    group.bench_function("in-memory-blocked-tree", |b| {
+        b.iter(|| {
+            for ip in ips.iter() {
+                let _ = reader.lookup::<maxminddb::City>(*ip);
+            }
+        })
+    });
+
+    let reader = maxminddb::ReaderBuilder::new()
+        .ipv4_ranges(true)
+        .open_file(path)
+        .unwrap();
+    group.bench_function("in-memory-ipv4-ranges", |b| {
+        b.iter(|| {
+            for ip in ips.iter() {
+                let _ = reader.lookup::<maxminddb::City>(*ip);
+            }
+        })
+    });
+
     let reader = maxminddb::Reader::mmap(path).unwrap();
     group.bench_function("mmap", |b| {
         b.iter(|| {
@@ -132,6 +168,15 @@ fn bench(c: &mut Criterion) {
         })
     });
 
+    let mut city = maxminddb::City::default();
+    group.bench_function("mmap-lookup-into", |b| {
+        b.iter(|| {
+            for ip in ips.iter() {
+                let _ = reader.lookup_into(&mut city, *ip);
+            }
+        })
+    });
+
     let data = std::fs::read(path).unwrap();
     let reader = ::geoip2::Reader::<::geoip2::City>::from_bytes(&data).unwrap();
     group.bench_function("geoip2", |b| {
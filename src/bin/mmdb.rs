@@ -0,0 +1,131 @@
+use std::net::IpAddr;
+
+use maxminddb::schema::Schema;
+use maxminddb::{Error, Network, Reader};
+
+const USAGE: &str = "\
+usage: mmdb <command> [args]
+
+commands:
+    explain <db> <ip>    trace a single lookup step by step
+    disasm <db>          print every value of the data section
+    dot <db> [network] [max-depth]
+                         export the search tree as a Graphviz DOT graph
+    schema <db> [--json] infer the schema of every record, as text or JSON Schema
+    codegen <db|schema> [name]
+                         generate Rust record types and their decoders";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        ["explain", path, ip] => explain(path, ip),
+        ["disasm", path] => disasm(path),
+        ["dot", path] => dot(path, None, None),
+        ["dot", path, network] => dot(path, Some(network), None),
+        ["dot", path, network, max_depth] => dot(path, Some(network), Some(max_depth)),
+        ["schema", path] => schema(path, false),
+        ["schema", path, "--json"] => schema(path, true),
+        ["codegen", path] => codegen(path, None),
+        ["codegen", path, name] => codegen(path, Some(name)),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+fn explain(path: &str, ip: &str) -> Result<(), String> {
+    let reader = Reader::open_file(path).map_err(|err| err.to_string())?;
+    let ip: IpAddr = ip
+        .parse()
+        .map_err(|err| format!("invalid ip {ip}, {err}"))?;
+    let explain = reader.explain(ip).map_err(|err| err.to_string())?;
+
+    print!("{explain}");
+    Ok(())
+}
+
+fn disasm(path: &str) -> Result<(), String> {
+    let reader = Reader::open_file(path).map_err(|err| err.to_string())?;
+
+    let mut issues = 0;
+    for instruction in reader.disassemble() {
+        if instruction.issue.is_some() {
+            issues += 1;
+        }
+
+        println!("{instruction}");
+    }
+
+    if issues > 0 {
+        return Err(format!("{issues} issues found"));
+    }
+
+    Ok(())
+}
+
+fn dot(path: &str, network: Option<&str>, max_depth: Option<&str>) -> Result<(), String> {
+    let reader = Reader::open_file(path).map_err(|err| err.to_string())?;
+    let network: Network = match network {
+        Some(network) => network
+            .parse()
+            .map_err(|err| format!("invalid network {network}, {err}"))?,
+        None => {
+            let metadata = reader.metadata().map_err(|err| err.to_string())?;
+            if metadata.ip_version == 6 {
+                "::/0"
+            } else {
+                "0.0.0.0/0"
+            }
+            .parse()
+            .unwrap()
+        }
+    };
+    let max_depth = match max_depth {
+        Some(max_depth) => max_depth
+            .parse()
+            .map_err(|err| format!("invalid max depth {max_depth}, {err}"))?,
+        None => 8,
+    };
+
+    let dot = reader
+        .export_dot(network, max_depth)
+        .map_err(|err| err.to_string())?;
+    print!("{dot}");
+
+    Ok(())
+}
+
+fn schema(path: &str, json: bool) -> Result<(), String> {
+    let reader = Reader::open_file(path).map_err(|err| err.to_string())?;
+    let schema = reader.infer_schema().map_err(|err| err.to_string())?;
+
+    if json {
+        schema
+            .write_json_schema(&mut std::io::stdout().lock())
+            .map_err(|err| err.to_string())
+    } else {
+        print!("{schema}");
+        Ok(())
+    }
+}
+
+// `path` is a database, or a schema as printed by `mmdb schema`
+fn codegen(path: &str, name: Option<&str>) -> Result<(), String> {
+    let schema: Schema = match Reader::open_file(path) {
+        Ok(reader) => reader.infer_schema().map_err(|err| err.to_string())?,
+        Err(Error::MetadataNotFound) => std::fs::read_to_string(path)
+            .map_err(|err| format!("read {path} failed, {err}"))?
+            .parse()
+            .map_err(|err: Error| err.to_string())?,
+        Err(err) => return Err(err.to_string()),
+    };
+    let name = name.unwrap_or(&schema.database_type);
+
+    print!("{}", schema.to_rust(name));
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use crate::{Error, Reader};
+
+/// Options to open a [`Reader`] with.
+///
+/// ```no_run
+/// let reader = maxminddb::ReaderBuilder::new()
+///     .jump_table(16)
+///     .open_file("GeoLite2-City.mmdb")?;
+/// # Ok::<(), maxminddb::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ReaderBuilder {
+    jump_table_bits: Option<u8>,
+    blocked_tree: bool,
+    ipv4_ranges: bool,
+}
+
+impl ReaderBuilder {
+    pub fn new() -> Self {
+        ReaderBuilder::default()
+    }
+
+    /// Resolve the first `bits` bits of an address with a single array
+    /// access instead of walking as many nodes of the search tree. The
+    /// table takes `4 << bits` bytes for IPv4 lookups, as much again for
+    /// IPv6 lookups in an IPv6 database, 256KiB each with 16 bits.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is not within `1..=24`.
+    pub fn jump_table(mut self, bits: u8) -> Self {
+        assert!(
+            (1..=24).contains(&bits),
+            "jump table bits must be within 1..=24"
+        );
+        self.jump_table_bits = Some(bits);
+        self
+    }
+
+    /// Copy the search tree at open time into a layout where lookups touch
+    /// fewer cache lines: the nodes of every 3 levels are next to each
+    /// other, with native 32 bits records. It takes 8 bytes per node, about
+    /// 30MiB for GeoLite2-City. Records are still decoded from the data
+    /// section of the database.
+    pub fn blocked_tree(mut self, blocked_tree: bool) -> Self {
+        self.blocked_tree = blocked_tree;
+        self
+    }
+
+    /// Compile the IPv4 part of the database at open time into a sorted
+    /// table of address ranges, and lookup IPv4 addresses with a search in
+    /// it instead of walking the tree bit by bit. Adjacent ranges with the
+    /// same record are merged, every range takes 12 bytes.
+    pub fn ipv4_ranges(mut self, ipv4_ranges: bool) -> Self {
+        self.ipv4_ranges = ipv4_ranges;
+        self
+    }
+
+    /// Open a database from anything that implements AsRef<[u8]>
+    pub fn from_bytes<S: AsRef<[u8]>>(self, buf: S) -> Result<Reader<S>, Error> {
+        let mut reader = Reader::from_bytes(buf)?;
+        if self.blocked_tree {
+            reader.blocked_tree = Some(BlockedTree::build(&reader));
+        }
+        if let Some(bits) = self.jump_table_bits {
+            reader.jump_table = Some(JumpTable::build(&reader, bits));
+        }
+        if self.ipv4_ranges {
+            reader.ipv4_ranges = Some(RangeTable::build(&reader));
+        }
+
+        Ok(reader)
+    }
+
+    /// Open a MaxMind DB file by loading it into memory.
+    pub fn open_file(self, path: impl AsRef<Path>) -> Result<Reader<Vec<u8>>, Error> {
+        self.from_bytes(std::fs::read(path)?)
+    }
+
+    /// Open a MaxMind DB file by mmaping it.
+    #[cfg(feature = "mmap")]
+    pub fn mmap(self, path: impl AsRef<Path>) -> Result<Reader<memmap2::Mmap>, Error> {
+        let file = std::fs::File::open(path)?;
+        let mmap = unsafe { memmap2::MmapOptions::new().map(&file) }?;
+        self.from_bytes(mmap)
+    }
+}
+
+/// The node, or the record of a leaf, reached after the first `bits` bits
+/// of every address.
+pub(crate) struct JumpTable {
+    bits: u8,
+    ipv4: Vec<u32>,
+    // empty in an IPv4 database
+    ipv6: Vec<u32>,
+}
+
+impl JumpTable {
+    fn build<S: AsRef<[u8]>>(reader: &Reader<S>, bits: u8) -> Self {
+        // the nodes of the tree lookups walk, which may have been relaid out
+        let (ip_v4_start, read_node): (usize, &dyn Fn(usize, usize) -> usize) =
+            match &reader.blocked_tree {
+                Some(tree) => (tree.ip_v4_start, &|node, bit| {
+                    tree.nodes[node][bit] as usize
+                }),
+                None => (reader.ip_v4_start, &|node, bit| reader.read_node(node, bit)),
+            };
+        let fill = |entries: &mut [u32], root| {
+            fill(entries, read_node, reader.node_count, root, 0, 0, bits)
+        };
+
+        let mut ipv4 = vec![0; 1 << bits];
+        fill(&mut ipv4, ip_v4_start);
+
+        let mut ipv6 = Vec::new();
+        if reader.ip_v4_start != 0 {
+            ipv6 = vec![0; 1 << bits];
+            fill(&mut ipv6, 0);
+        }
+
+        JumpTable { bits, ipv4, ipv6 }
+    }
+
+    /// The node to continue from and the number of bits of `ip` it covers.
+    #[inline]
+    pub(crate) fn start(&self, ip: &[u8]) -> Option<(usize, usize)> {
+        let entries = if ip.len() == 16 {
+            &self.ipv6
+        } else {
+            &self.ipv4
+        };
+
+        let index = u32::from_be_bytes([0, ip[0], ip[1], ip[2]]) >> (24 - self.bits);
+        entries
+            .get(index as usize)
+            .map(|node| (*node as usize, self.bits as usize))
+    }
+}
+
+fn fill(
+    entries: &mut [u32],
+    read_node: &dyn Fn(usize, usize) -> usize,
+    node_count: usize,
+    node: usize,
+    depth: u8,
+    prefix: usize,
+    bits: u8,
+) {
+    // a leaf before the last level covers every address under it
+    if node >= node_count || depth == bits {
+        let shift = bits - depth;
+        entries[prefix << shift..(prefix + 1) << shift].fill(node as u32);
+        return;
+    }
+
+    for bit in 0..2 {
+        let child = read_node(node, bit);
+        let prefix = prefix << 1 | bit;
+        fill(
+            entries,
+            read_node,
+            node_count,
+            child,
+            depth + 1,
+            prefix,
+            bits,
+        );
+    }
+}
+
+// levels of the tree kept next to each other, 7 nodes of 8 bytes fit in a
+// cache line
+const BLOCK_HEIGHT: usize = 3;
+
+/// The search tree with its nodes renumbered so that every subtree of
+/// `BLOCK_HEIGHT` levels is contiguous. The node count is unchanged, so
+/// leaf records still point at the data section as in the file.
+pub(crate) struct BlockedTree {
+    pub(crate) nodes: Vec<[u32; 2]>,
+    pub(crate) ip_v4_start: usize,
+}
+
+impl BlockedTree {
+    fn build<S: AsRef<[u8]>>(reader: &Reader<S>) -> Self {
+        let node_count = reader.node_count;
+        const UNPLACED: u32 = u32::MAX;
+
+        // new number of every node, and the node at every new number
+        let mut ids = vec![UNPLACED; node_count];
+        let mut order = Vec::with_capacity(node_count);
+
+        // blocks are placed breadth first, the nodes of a block too. Nodes
+        // reachable through several paths, like the IPv4 subtree of an IPv6
+        // tree, are placed in the first block reaching them.
+        let mut blocks = VecDeque::from([0]);
+        while let Some(root) = blocks.pop_front() {
+            let mut level = vec![root];
+            for depth in 0..BLOCK_HEIGHT {
+                let mut next = Vec::new();
+                for node in level {
+                    if ids[node] != UNPLACED {
+                        continue;
+                    }
+
+                    ids[node] = order.len() as u32;
+                    order.push(node);
+                    for bit in 0..2 {
+                        let child = reader.read_node(node, bit);
+                        if child >= node_count {
+                            continue;
+                        } else if depth + 1 < BLOCK_HEIGHT {
+                            next.push(child);
+                        } else {
+                            blocks.push_back(child);
+                        }
+                    }
+                }
+                level = next;
+            }
+        }
+
+        // nodes no lookup can reach
+        for (node, id) in ids.iter_mut().enumerate() {
+            if *id == UNPLACED {
+                *id = order.len() as u32;
+                order.push(node);
+            }
+        }
+
+        let renumber = |node: usize| match ids.get(node) {
+            Some(id) => *id,
+            None => node as u32,
+        };
+        let nodes = order
+            .iter()
+            .map(|&node| [0, 1].map(|bit| renumber(reader.read_node(node, bit))))
+            .collect();
+
+        BlockedTree {
+            nodes,
+            ip_v4_start: renumber(reader.ip_v4_start) as usize,
+        }
+    }
+}
+
+/// The IPv4 address ranges with a record, sorted and laid out in
+/// Eytzinger order: the children of the range at `k` are at `2k` and
+/// `2k + 1`, so a search reads the first levels from the same cache lines
+/// every time. The ends are kept apart, 16 of them to a cache line.
+pub(crate) struct RangeTable {
+    // 1-based, the first entries are unused
+    ends: Vec<u32>,
+    starts: Vec<u32>,
+    records: Vec<u32>,
+}
+
+impl RangeTable {
+    fn build<S: AsRef<[u8]>>(reader: &Reader<S>) -> Self {
+        let mut ranges: Vec<(u32, u32, u32)> = Vec::new();
+
+        let mut stack = vec![(reader.ip_v4_start, 0u32, 0u64)];
+        while let Some((node, depth, prefix)) = stack.pop() {
+            if node < reader.node_count {
+                // a node at the last level is corrupt, its addresses are
+                // left out like the ones without data
+                if depth < 32 {
+                    // the left child is popped first, the ranges are sorted
+                    stack.push((reader.read_node(node, 1), depth + 1, prefix << 1 | 1));
+                    stack.push((reader.read_node(node, 0), depth + 1, prefix << 1));
+                }
+                continue;
+            } else if node == reader.node_count {
+                continue;
+            }
+
+            let start = (prefix << (32 - depth)) as u32;
+            let end = (((prefix + 1) << (32 - depth)) - 1) as u32;
+            match ranges.last_mut() {
+                Some(last) if last.1.checked_add(1) == Some(start) && last.2 == node as u32 => {
+                    last.1 = end
+                }
+                _ => ranges.push((start, end, node as u32)),
+            }
+        }
+
+        RangeTable::from_sorted(&ranges)
+    }
+
+    fn from_sorted(ranges: &[(u32, u32, u32)]) -> Self {
+        let mut table = RangeTable {
+            ends: vec![0; ranges.len() + 1],
+            starts: vec![0; ranges.len() + 1],
+            records: vec![0; ranges.len() + 1],
+        };
+        table.place(ranges, &mut 0, 1);
+        table
+    }
+
+    // in-order walk of the implicit tree, assigning the sorted ranges
+    fn place(&mut self, ranges: &[(u32, u32, u32)], next: &mut usize, k: usize) {
+        if k > ranges.len() {
+            return;
+        }
+
+        self.place(ranges, next, 2 * k);
+        (self.starts[k], self.ends[k], self.records[k]) = ranges[*next];
+        *next += 1;
+        self.place(ranges, next, 2 * k + 1);
+    }
+
+    /// The record of the range holding `ip`, or 0 like an address without
+    /// data in the tree.
+    #[inline]
+    pub(crate) fn find(&self, ip: u32) -> usize {
+        // the first range ending at or after `ip`
+        let mut k = 1;
+        while k < self.ends.len() {
+            k = 2 * k + usize::from(self.ends[k] < ip);
+        }
+        k >>= k.trailing_ones() + 1;
+
+        if k != 0 && self.starts[k] <= ip {
+            self.records[k] as usize
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeTable;
+
+    #[test]
+    fn range_table() {
+        for count in 0..40u32 {
+            // ranges of 3 addresses, every other range has no record
+            let ranges: Vec<_> = (0..count).map(|i| (i * 6, i * 6 + 2, 100 + i)).collect();
+            let table = RangeTable::from_sorted(&ranges);
+
+            for ip in 0..count * 6 + 6 {
+                let expected = match ip % 6 {
+                    0..=2 if ip / 6 < count => 100 + ip / 6,
+                    _ => 0,
+                };
+                assert_eq!(table.find(ip), expected as usize, "{count} ranges, {ip}");
+            }
+        }
+
+        let table = RangeTable::from_sorted(&[(0, u32::MAX, 7)]);
+        assert_eq!(table.find(0), 7);
+        assert_eq!(table.find(u32::MAX), 7);
+    }
+}
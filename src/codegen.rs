@@ -0,0 +1,333 @@
+//! Rust record types generated from a schema, shaped like the ones of
+//! [`models`](crate::models).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::schema::{Field, Schema};
+
+// rustfmt breaks match arms longer than this into a block
+const MAX_WIDTH: usize = 100;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+// can't be raw identifiers
+const RESERVED: &[&str] = &["crate", "self", "Self", "super"];
+
+// types the generated code uses by name, and `Self`
+const TYPES: &[&str] = &[
+    "Data", "Decoder", "Error", "Option", "Result", "Self", "Vec",
+];
+
+impl Schema {
+    /// Generate a struct for every map of the records and its [`Decoder`]
+    /// impl, the record itself named `name`. Every field is an `Option`,
+    /// `names` maps of strings are [`Names`], maps and arrays of another
+    /// shape get their own struct, and fields seen with types no single
+    /// Rust type decodes are a [`Value`]. Structs of the same shape are
+    /// generated once.
+    ///
+    /// The code uses `maxminddb` by name, so it can be written by a build
+    /// script and included in a module of its own:
+    ///
+    /// ```no_run
+    /// // build.rs
+    /// use maxminddb::Reader;
+    ///
+    /// let reader = Reader::open_file("vendor.mmdb").unwrap();
+    /// let schema = reader.infer_schema().unwrap();
+    /// let out = std::env::var("OUT_DIR").unwrap();
+    /// std::fs::write(format!("{out}/vendor.rs"), schema.to_rust("Vendor")).unwrap();
+    /// ```
+    ///
+    /// [`Decoder`]: crate::Decoder
+    /// [`Names`]: crate::models::Names
+    /// [`Value`]: crate::Value
+    pub fn to_rust(&self, name: &str) -> String {
+        let mut codegen = Codegen::default();
+        let mut name = type_name(name, "Record");
+        if TYPES.contains(&name.as_str()) {
+            name.push_str("Record");
+        }
+        // nested structs can't take the name of the record
+        codegen.names.insert(name.clone(), ROOT.to_string());
+        let record = codegen.rust_type(&self.root, &name, "");
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// Generated from the schema of {}.",
+            self.database_type
+        )
+        .unwrap();
+        out.push('\n');
+        out.push_str("use maxminddb::{Data, Decoder, Error};\n");
+        if record.trim_end_matches("<'a>") != name {
+            let lifetime = if record.contains("'a") { "<'a>" } else { "" };
+            write!(out, "\npub type {name}{lifetime} = {record};\n").unwrap();
+        }
+        for code in codegen.structs.iter().rev() {
+            out.push('\n');
+            out.push_str(code);
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct Codegen {
+    structs: Vec<String>,
+    // the struct generated for every shape, by its fields
+    shapes: HashMap<String, String>,
+    names: HashMap<String, String>,
+}
+
+impl Codegen {
+    // the type of `field`, `name` is the struct a map would be
+    fn rust_type(&mut self, field: &Field, name: &str, parent: &str) -> String {
+        let types = field.types.keys().copied().collect::<Vec<_>>();
+        match types.as_slice() {
+            ["map"] => self.map(field, name, parent),
+            ["array"] => {
+                let item = match &field.items {
+                    Some(items) => self.rust_type(items, &singular(name), parent),
+                    None => VALUE.to_string(),
+                };
+                format!("Vec<{item}>")
+            }
+            ["utf8_string"] => "&'a str".to_string(),
+            ["bytes"] => "&'a [u8]".to_string(),
+            ["boolean"] => "bool".to_string(),
+            ["double"] => "f64".to_string(),
+            ["float"] => "f32".to_string(),
+            types => integer_type(types).unwrap_or(VALUE).to_string(),
+        }
+    }
+
+    fn map(&mut self, field: &Field, name: &str, parent: &str) -> String {
+        if name.ends_with("Names")
+            && !field.fields.is_empty()
+            && field.fields.values().all(|names| {
+                names.types.keys().eq(["utf8_string"].iter()) && names.fields.is_empty()
+            })
+        {
+            return "maxminddb::models::Names<'a>".to_string();
+        }
+
+        let mut fields = Vec::new();
+        let mut idents = Vec::<String>::new();
+        for (key, child) in &field.fields {
+            let mut ident = ident(&snake_case(key));
+            let base = ident.clone();
+            let mut n = 2;
+            while idents.contains(&ident) {
+                ident = format!("{base}_{n}");
+                n += 1;
+            }
+            idents.push(ident.clone());
+
+            let child_type = self.rust_type(child, &type_name(key, name), name);
+            fields.push((key.as_str(), ident, child_type));
+        }
+
+        let mut shape = String::new();
+        for (key, ident, child_type) in &fields {
+            writeln!(shape, "{key:?} {ident} {child_type}").unwrap();
+        }
+        if let Some(existing) = self.shapes.get(&shape) {
+            return existing.clone();
+        }
+
+        let name = self.unique_name(name, parent, &shape);
+        let lifetime = if shape.contains("'a") { "<'a>" } else { "" };
+        let var = ident(&snake_case(&name));
+        let mut code = String::new();
+        code.push_str("#[derive(Debug, Default)]\n");
+        writeln!(code, "pub struct {name}{lifetime} {{").unwrap();
+        for (_, ident, child_type) in &fields {
+            writeln!(code, "    pub {ident}: Option<{child_type}>,").unwrap();
+        }
+        code.push_str("}\n\n");
+
+        writeln!(code, "impl<'a> Decoder<'a> for {name}{lifetime} {{").unwrap();
+        code.push_str(
+            "    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {\n",
+        );
+        let binding = if fields.is_empty() { "let" } else { "let mut" };
+        writeln!(code, "        {binding} {var} = {name}::default();").unwrap();
+        code.push('\n');
+        code.push_str("        for _ in 0..size {\n");
+        code.push_str("            match <&str>::decode(buf, offset)? {\n");
+        for (key, ident, child_type) in &fields {
+            let value = format!("Some({}::decode(buf, offset)?)", type_path(child_type));
+            let arm = format!("                {key:?} => {var}.{ident} = {value},");
+            let assign = format!("                    {var}.{ident} = {value}");
+            if arm.len() <= MAX_WIDTH {
+                writeln!(code, "{arm}").unwrap();
+            } else if assign.len() <= MAX_WIDTH {
+                writeln!(
+                    code,
+                    "                {key:?} => {{\n{assign}\n                }}"
+                )
+                .unwrap();
+            } else {
+                writeln!(code, "                {key:?} => {{").unwrap();
+                writeln!(code, "                    {var}.{ident} =").unwrap();
+                writeln!(code, "                        {value}").unwrap();
+                code.push_str("                }\n");
+            }
+        }
+        code.push_str(
+            "                field => return Err(Error::UnknownField(field.to_string())),\n",
+        );
+        code.push_str("            }\n");
+        code.push_str("        }\n");
+        code.push('\n');
+        writeln!(code, "        Ok({var})").unwrap();
+        code.push_str("    }\n");
+        code.push_str("}\n");
+
+        let rust_type = format!("{name}{lifetime}");
+        self.structs.push(code);
+        self.shapes.insert(shape.clone(), rust_type.clone());
+        self.names.insert(name, shape);
+        rust_type
+    }
+
+    // `name`, or prefixed with its parent if another shape has it already
+    // or the generated code uses it
+    fn unique_name(&self, name: &str, parent: &str, shape: &str) -> String {
+        if parent.is_empty() && self.names.get(name).map(String::as_str) == Some(ROOT) {
+            return name.to_string();
+        }
+
+        let mut candidates = vec![name.to_string()];
+        if !parent.is_empty() && !name.starts_with(parent) {
+            candidates.push(format!("{parent}{name}"));
+        }
+        for candidate in &candidates {
+            if !TYPES.contains(&candidate.as_str())
+                && self.names.get(candidate).is_none_or(|other| other == shape)
+            {
+                return candidate.clone();
+            }
+        }
+
+        let base = candidates.pop().unwrap();
+        let mut n = 2;
+        while self.names.contains_key(&format!("{base}{n}")) {
+            n += 1;
+        }
+        format!("{base}{n}")
+    }
+}
+
+const VALUE: &str = "maxminddb::Value<'a>";
+
+// the shape of the name reserved for the record
+const ROOT: &str = "\0record";
+
+// the narrowest integer holding every integer type seen
+fn integer_type(types: &[&str]) -> Option<&'static str> {
+    let mut unsigned = 0;
+    let mut signed = false;
+    for &name in types {
+        match name {
+            "uint16" => unsigned = unsigned.max(16),
+            "uint32" => unsigned = unsigned.max(32),
+            "uint64" => unsigned = unsigned.max(64),
+            "uint128" => unsigned = unsigned.max(128),
+            "int32" => signed = true,
+            _ => return None,
+        }
+    }
+
+    Some(match (signed, unsigned) {
+        (false, 16) => "u16",
+        (false, 32) => "u32",
+        (false, 64) => "u64",
+        (false, 128) => "u128",
+        (true, 0) => "i32",
+        (true, 16 | 32) => "i64",
+        (true, 64) => "i128",
+        // nothing seen, or int32 along with uint128
+        _ => return None,
+    })
+}
+
+// a type usable as the path of `decode`
+fn type_path(rust_type: &str) -> String {
+    if rust_type.contains(['<', '&']) {
+        format!("<{rust_type}>")
+    } else {
+        rust_type.to_string()
+    }
+}
+
+fn ident(key: &str) -> String {
+    let mut ident = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if RESERVED.contains(&ident.as_str()) {
+        ident.push('_');
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+    ident
+}
+
+// `autonomous_system` as `AutonomousSystem`, `parent` prepended if it
+// doesn't start with a letter
+fn type_name(key: &str, parent: &str) -> String {
+    let mut name = String::new();
+    for part in key.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars);
+        }
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, parent);
+    }
+    name
+}
+
+// the type of the elements of an array, `Subdivisions` as `Subdivision`
+fn singular(name: &str) -> String {
+    match name.strip_suffix('s') {
+        Some(singular) if !singular.is_empty() && !singular.ends_with('s') => singular.to_string(),
+        _ => format!("{name}Item"),
+    }
+}
+
+// `mapX` as `map_x`, `pt-BR` as `pt_br`
+fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 && !snake.ends_with('_') {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if !previous.is_ascii_uppercase() || next_lower {
+                snake.push('_');
+            }
+        }
+        snake.push(if c.is_ascii_alphanumeric() {
+            c.to_ascii_lowercase()
+        } else {
+            '_'
+        });
+    }
+    snake
+}
@@ -1,10 +1,14 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::key::{Key, KeyCache};
 use crate::Error;
 
 pub(crate) const DATA_TYPE_EXTENDED: u8 = 0;
 pub(crate) const DATA_TYPE_POINTER: u8 = 1;
 pub(crate) const DATA_TYPE_STRING: u8 = 2;
 pub(crate) const DATA_TYPE_FLOAT64: u8 = 3;
-// pub(crate) const DATA_TYPE_BYTES: u8 = 4;
+pub(crate) const DATA_TYPE_BYTES: u8 = 4;
 pub(crate) const DATA_TYPE_UINT16: u8 = 5;
 pub(crate) const DATA_TYPE_UINT32: u8 = 6;
 pub(crate) const DATA_TYPE_MAP: u8 = 7;
@@ -12,20 +16,155 @@ pub(crate) const DATA_TYPE_INT32: u8 = 8;
 pub(crate) const DATA_TYPE_UINT64: u8 = 9;
 pub(crate) const DATA_TYPE_UINT128: u8 = 10;
 pub(crate) const DATA_TYPE_SLICE: u8 = 11;
-// pub(crate) const DATA_TYPE_DATA_CACHE_CONTAINER: u8 = 12;
-// pub(crate) const DATA_TYPE_END_MARKER: u8 = 13;
+pub(crate) const DATA_TYPE_DATA_CACHE_CONTAINER: u8 = 12;
+pub(crate) const DATA_TYPE_END_MARKER: u8 = 13;
 pub(crate) const DATA_TYPE_BOOL: u8 = 14;
-// pub(crate) const DATA_TYPE_FLOAT32: u8 = 15;
+pub(crate) const DATA_TYPE_FLOAT32: u8 = 15;
+
+/// Maps and arrays nested deeper than this are rejected, the GeoIP2 records
+/// are 4 levels deep and only a pointer cycle should get this far.
+pub(crate) const MAX_DEPTH: usize = 32;
+
+/// Human readable name of a data type, as used by the MaxMind DB spec.
+pub(crate) fn type_name(data_type: u8) -> &'static str {
+    match data_type {
+        DATA_TYPE_EXTENDED => "extended",
+        DATA_TYPE_POINTER => "pointer",
+        DATA_TYPE_STRING => "utf8_string",
+        DATA_TYPE_FLOAT64 => "double",
+        DATA_TYPE_BYTES => "bytes",
+        DATA_TYPE_UINT16 => "uint16",
+        DATA_TYPE_UINT32 => "uint32",
+        DATA_TYPE_MAP => "map",
+        DATA_TYPE_INT32 => "int32",
+        DATA_TYPE_UINT64 => "uint64",
+        DATA_TYPE_UINT128 => "uint128",
+        DATA_TYPE_SLICE => "array",
+        DATA_TYPE_DATA_CACHE_CONTAINER => "data_cache_container",
+        DATA_TYPE_END_MARKER => "end_marker",
+        DATA_TYPE_BOOL => "boolean",
+        DATA_TYPE_FLOAT32 => "float",
+        _ => "unknown",
+    }
+}
+
+/// The bytes values are decoded from. The data of a reader also carries the
+/// key of every key pointer it has read, so a record doesn't match its keys
+/// again, and once verified the values whose strings are known to be UTF-8.
+/// [`Data::new`] wraps any other bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct Data<'a> {
+    bytes: &'a [u8],
+    keys: Option<&'a KeyCache>,
+    // the value starts found by `verify_values` on `bytes`
+    starts: Option<&'a [u64]>,
+}
+
+impl<'a> Data<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Data {
+            bytes,
+            keys: None,
+            starts: None,
+        }
+    }
+
+    /// The data section of a reader, decoded with its key cache and, if it
+    /// was verified, its value starts.
+    pub(crate) fn of_reader(
+        bytes: &'a [u8],
+        keys: &'a KeyCache,
+        starts: Option<&'a [u64]>,
+    ) -> Self {
+        Data {
+            bytes,
+            keys: Some(keys),
+            starts,
+        }
+    }
+
+    pub fn bytes(self) -> &'a [u8] {
+        self.bytes
+    }
 
+    /// Read the `size` bytes at `offset` as the string whose control byte is
+    /// at `start`. They are only validated again if `start` isn't the start
+    /// of a verified string of that size.
+    #[inline]
+    pub(crate) fn read_string(
+        self,
+        start: usize,
+        offset: &mut usize,
+        size: usize,
+    ) -> Result<&'a str, Error> {
+        let data_start = *offset;
+        let data = read_bytes(self.bytes, offset, size)?;
+        if let Some(starts) = self.starts {
+            let mut control = start;
+            if is_value_start(starts, start)
+                && read_control(self.bytes, &mut control).ok() == Some((DATA_TYPE_STRING, size))
+                && control == data_start
+            {
+                // SAFETY: `verify_values` validated the string of every
+                // value start of these bytes, and this is one of them
+                return Ok(unsafe { std::str::from_utf8_unchecked(data) });
+            }
+        }
+
+        bytes_to_str(data)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Data<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Data::new(bytes)
+    }
+}
+
+/// Decode a value of the data section, borrowing strings and bytes from
+/// it. Records are maps, decoded by `decode_with_size` once their size is
+/// read, which is all a record type needs to implement. Its fields can be
+/// any type implementing `Decoder`, the standard ones included:
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use maxminddb::{Data, Decoder, Error};
+///
+/// struct Place<'a> {
+///     names: BTreeMap<&'a str, &'a str>,
+///     geoname_id: Option<u32>,
+/// }
+///
+/// impl<'a> Decoder<'a> for Place<'a> {
+///     fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+///         let mut place = Place {
+///             names: BTreeMap::new(),
+///             geoname_id: None,
+///         };
+///         for _ in 0..size {
+///             match <&str>::decode(buf, offset)? {
+///                 "names" => place.names = BTreeMap::decode(buf, offset)?,
+///                 "geoname_id" => place.geoname_id = Option::decode(buf, offset)?,
+///                 field => return Err(Error::UnknownField(field.into())),
+///             }
+///         }
+///
+///         Ok(place)
+///     }
+/// }
+/// ```
 pub trait Decoder<'a>: Sized {
-    fn decode(buf: &'a [u8], offset: &mut usize) -> Result<Self, Error> {
-        let (data_type, size) = read_control(buf, offset)?;
+    /// Decode the value at `offset`, following a pointer to it, and move
+    /// `offset` past it.
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        let (data_type, size) = read_control(buf.bytes, offset)?;
 
         match data_type {
             DATA_TYPE_MAP => Self::decode_with_size(buf, offset, size),
             DATA_TYPE_POINTER => {
-                let offset = &mut read_pointer(buf, offset, size)?;
-                let (data_type, size) = read_control(buf, offset)?;
+                let offset = &mut read_pointer(buf.bytes, offset, size)?;
+                let (data_type, size) = read_control(buf.bytes, offset)?;
                 match data_type {
                     // NOTE: The `offset` here is not the argument anymore.
                     DATA_TYPE_MAP => Self::decode_with_size(buf, offset, size),
@@ -36,26 +175,65 @@ pub trait Decoder<'a>: Sized {
         }
     }
 
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error>;
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error>;
+
+    /// Like `decode_with_size`, but overwrite `self` so the allocations it
+    /// holds can be reused.
+    fn decode_into(&mut self, buf: Data<'a>, offset: &mut usize, size: usize) -> Result<(), Error> {
+        *self = Self::decode_with_size(buf, offset, size)?;
+        Ok(())
+    }
+
+    /// Like `decode`, but overwrite `self` so the allocations it holds can
+    /// be reused.
+    fn decode_in_place(&mut self, buf: Data<'a>, offset: &mut usize) -> Result<(), Error> {
+        *self = Self::decode(buf, offset)?;
+        Ok(())
+    }
+}
+
+/// Read the control byte of the value at `offset`, or of the one a pointer
+/// there points to, and decode its data with `f`.
+#[inline]
+pub(crate) fn decode_value<T>(
+    buf: &[u8],
+    offset: &mut usize,
+    f: impl FnOnce(u8, usize, &mut usize) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let (data_type, size) = read_control(buf, offset)?;
+    if data_type == DATA_TYPE_POINTER {
+        // NOTE: a pointer to a pointer is invalid, `f` rejects it
+        let target = &mut read_pointer(buf, offset, size)?;
+        let (data_type, size) = read_control(buf, target)?;
+        return f(data_type, size, target);
+    }
+
+    f(data_type, size, offset)
 }
 
 #[inline(always)]
 pub(crate) fn read_control(buf: &[u8], offset: &mut usize) -> Result<(u8, usize), Error> {
-    let control_byte = buf[*offset];
+    let control_byte = *buf.get(*offset).ok_or(Error::InvalidOffset)?;
     *offset += 1;
     let mut data_type = control_byte >> 5;
     if data_type == DATA_TYPE_EXTENDED {
-        data_type = buf[*offset] + 7;
+        let extended = *buf.get(*offset).ok_or(Error::InvalidOffset)?;
+        if extended == 0 || extended > DATA_TYPE_FLOAT32 - 7 {
+            return Err(Error::InvalidDataType(extended.wrapping_add(7)));
+        }
+
+        data_type = extended + 7;
         *offset += 1;
     }
     let mut size = (control_byte as usize) & 0x1f;
-    if data_type == DATA_TYPE_EXTENDED || size < 29 {
+    // the size bits of a pointer are part of the pointer itself
+    if data_type == DATA_TYPE_POINTER || size < 29 {
         return Ok((data_type, size));
     }
 
     match size - 28 {
         1 => {
-            if *offset > buf.len() - 1 {
+            if *offset + 1 > buf.len() {
                 return Err(Error::InvalidOffset);
             }
 
@@ -63,20 +241,20 @@ pub(crate) fn read_control(buf: &[u8], offset: &mut usize) -> Result<(u8, usize)
             *offset += 1;
         }
         2 => {
-            if *offset > buf.len() - 2 {
+            if *offset + 2 > buf.len() {
                 return Err(Error::InvalidOffset);
             }
 
             size = 285 + buf[*offset] as usize * 256 + buf[*offset + 1] as usize;
             *offset += 2;
         }
-        n => {
-            if *offset > buf.len() - n {
+        _ => {
+            if *offset + 3 > buf.len() {
                 return Err(Error::InvalidOffset);
             }
 
-            size = 65_821;
-            *offset += n;
+            size = 65_821 + bytes_to_usize(&buf[*offset..*offset + 3]);
+            *offset += 3;
         }
     }
 
@@ -84,25 +262,48 @@ pub(crate) fn read_control(buf: &[u8], offset: &mut usize) -> Result<(u8, usize)
 }
 
 #[inline]
-pub(crate) fn read_str<'a>(buf: &'a [u8], offset: &mut usize) -> Result<&'a str, Error> {
+pub(crate) fn read_str<'a>(buf: Data<'a>, offset: &mut usize) -> Result<&'a str, Error> {
+    let start = *offset;
+    let (data_type, size) = read_control(buf.bytes, offset)?;
+    match data_type {
+        DATA_TYPE_STRING => buf.read_string(start, offset, size),
+        DATA_TYPE_POINTER => {
+            let start = read_pointer(buf.bytes, offset, size)?;
+            let offset = &mut { start };
+            match read_control(buf.bytes, offset)? {
+                (DATA_TYPE_STRING, size) => buf.read_string(start, offset, size),
+                (data_type, _) => Err(Error::InvalidDataType(data_type)),
+            }
+        }
+        _ => Err(Error::InvalidDataType(data_type)),
+    }
+}
+
+/// Read a string already validated, like the ones of [`Names`] which are
+/// all read once when it is decoded.
+///
+/// # Safety
+///
+/// The string at `offset` must have been read with [`read_str`] before.
+///
+/// [`Names`]: crate::models::Names
+#[inline]
+pub(crate) unsafe fn read_str_unchecked<'a>(
+    buf: &'a [u8],
+    offset: &mut usize,
+) -> Result<&'a str, Error> {
     let (data_type, size) = read_control(buf, offset)?;
     let data = match data_type {
         DATA_TYPE_STRING => read_bytes(buf, offset, size)?,
         DATA_TYPE_POINTER => {
             let offset = &mut read_pointer(buf, offset, size)?;
-            let (data_type, size) = read_control(buf, offset)?;
-            match data_type {
-                DATA_TYPE_STRING => read_bytes(buf, offset, size)?,
-                _ => return Err(Error::InvalidDataType(data_type)),
-            }
+            let (_, size) = read_control(buf, offset)?;
+            read_bytes(buf, offset, size)?
         }
         _ => return Err(Error::InvalidDataType(data_type)),
     };
 
-    #[cfg(feature = "unsafe-str")]
-    return Ok(unsafe { std::str::from_utf8_unchecked(data) });
-    #[cfg(not(feature = "unsafe-str"))]
-    std::str::from_utf8(data).map_err(Error::InvalidUtf8)
+    Ok(std::str::from_utf8_unchecked(data))
 }
 
 #[inline]
@@ -115,11 +316,10 @@ pub(crate) fn read_pointer(buf: &[u8], offset: &mut usize, size: usize) -> Resul
 
     let unpacked = {
         let mut value = prefix;
-        for pos in *offset..*offset + pointer_size {
-            value = value << 8 | buf[pos] as usize;
+        for b in read_bytes(buf, offset, pointer_size)? {
+            value = value << 8 | *b as usize;
         }
 
-        *offset += pointer_size;
         value
     };
 
@@ -131,116 +331,149 @@ pub(crate) fn read_pointer(buf: &[u8], offset: &mut usize, size: usize) -> Resul
     Ok(unpacked + pointer_value_offset)
 }
 
-pub(crate) fn read_bool(buf: &[u8], offset: &mut usize) -> Result<bool, Error> {
-    let (data_type, size) = read_control(buf, offset)?;
+/// Walk every value of the data section, checking that the strings are
+/// UTF-8 and that the pointers point at the start of a value. Returns the
+/// offsets where a value starts, as a bitset.
+pub(crate) fn verify_values(buf: &[u8]) -> Result<Vec<u64>, Error> {
+    let mut starts = vec![0u64; buf.len().div_ceil(64)];
+    let mut pointers = Vec::new();
+
+    let mut offset = 0;
+    let mut remaining = 0usize;
+    while offset < buf.len() || remaining > 0 {
+        remaining = remaining.saturating_sub(1);
+        // a map or array with more values than the data section holds
+        if offset >= buf.len() {
+            return Err(Error::InvalidOffset);
+        }
+        starts[offset / 64] |= 1 << (offset % 64);
 
-    match data_type {
-        DATA_TYPE_BOOL => Ok(size != 0),
-        DATA_TYPE_POINTER => {
-            let offset = &mut read_pointer(buf, offset, size)?;
-            let (data_type, size) = read_control(buf, offset)?;
-            match data_type {
-                DATA_TYPE_BOOL => Ok(size != 0),
-                _ => Err(Error::InvalidDataType(data_type)),
+        let (data_type, size) = read_control(buf, &mut offset)?;
+        match data_type {
+            DATA_TYPE_POINTER => pointers.push(read_pointer(buf, &mut offset, size)?),
+            DATA_TYPE_STRING => {
+                let data = read_bytes(buf, &mut offset, size)?;
+                std::str::from_utf8(data).map_err(Error::InvalidUtf8)?;
+            }
+            DATA_TYPE_MAP => remaining += size * 2,
+            DATA_TYPE_SLICE => remaining += size,
+            DATA_TYPE_BOOL => {}
+            _ => {
+                read_bytes(buf, &mut offset, size)?;
             }
         }
-        _ => Err(Error::InvalidDataType(data_type)),
     }
+
+    if pointers
+        .into_iter()
+        .any(|pointer| !is_value_start(&starts, pointer))
+    {
+        return Err(Error::InvalidOffset);
+    }
+
+    Ok(starts)
 }
 
-pub(crate) fn read_f64(buf: &[u8], offset: &mut usize) -> Result<f64, Error> {
-    let (data_type, size) = read_control(buf, offset)?;
+#[inline]
+pub(crate) fn is_value_start(starts: &[u64], offset: usize) -> bool {
+    starts
+        .get(offset / 64)
+        .is_some_and(|bits| bits & (1 << (offset % 64)) != 0)
+}
 
-    #[inline(always)]
-    fn bytes_to_f64(buf: &[u8]) -> f64 {
-        let reserved: [u8; 8] = buf.try_into().unwrap();
-        f64::from_be_bytes(reserved)
-    }
+/// Move `offset` past the value at it without decoding it. Pointers are
+/// not followed, only their own bytes are skipped.
+pub(crate) fn skip_value(buf: &[u8], offset: &mut usize) -> Result<(), Error> {
+    let mut remaining = 1usize;
+    while remaining > 0 {
+        remaining -= 1;
 
-    match data_type {
-        DATA_TYPE_FLOAT64 => Ok(bytes_to_f64(read_bytes(buf, offset, size)?)),
-        DATA_TYPE_POINTER => {
-            let offset = &mut read_pointer(buf, offset, size)?;
-            let (data_type, size) = read_control(buf, offset)?;
-            match data_type {
-                DATA_TYPE_FLOAT64 => Ok(bytes_to_f64(read_bytes(buf, offset, size)?)),
-                _ => Err(Error::InvalidDataType(data_type)),
+        let (data_type, size) = read_control(buf, offset)?;
+        match data_type {
+            DATA_TYPE_POINTER => {
+                read_pointer(buf, offset, size)?;
+            }
+            DATA_TYPE_MAP => remaining += size * 2,
+            DATA_TYPE_SLICE => remaining += size,
+            DATA_TYPE_BOOL => {}
+            _ => {
+                read_bytes(buf, offset, size)?;
             }
         }
-        _ => Err(Error::InvalidDataType(data_type)),
     }
+
+    Ok(())
 }
 
-pub(crate) fn read_usize(buf: &[u8], offset: &mut usize) -> Result<usize, Error> {
-    let (data_type, size) = read_control(buf, offset)?;
-    let size = match data_type {
-        DATA_TYPE_UINT16 | DATA_TYPE_UINT32 | DATA_TYPE_INT32 | DATA_TYPE_UINT64
-        | DATA_TYPE_UINT128 => size,
-        DATA_TYPE_POINTER => {
-            let offset = &mut read_pointer(buf, offset, size)?;
-            let (data_type, size) = read_control(buf, offset)?;
-            match data_type {
-                DATA_TYPE_UINT16 | DATA_TYPE_UINT32 | DATA_TYPE_INT32 | DATA_TYPE_UINT64
-                | DATA_TYPE_UINT128 => size,
-                _ => return Err(Error::InvalidDataType(data_type)),
-            }
-        }
-        _ => return Err(Error::InvalidDataType(data_type)),
-    };
+pub(crate) fn read_bool(buf: Data<'_>, offset: &mut usize) -> Result<bool, Error> {
+    decode_value(buf.bytes, offset, |data_type, size, _| match data_type {
+        DATA_TYPE_BOOL => Ok(size != 0),
+        _ => Err(Error::InvalidDataType(data_type)),
+    })
+}
 
-    if size == 0 {
-        return Ok(0);
-    }
+pub(crate) fn read_f64(buf: Data<'_>, offset: &mut usize) -> Result<f64, Error> {
+    decode_value(
+        buf.bytes,
+        offset,
+        |data_type, size, offset| match data_type {
+            DATA_TYPE_FLOAT64 => bytes_to_f64(read_bytes(buf.bytes, offset, size)?),
+            _ => Err(Error::InvalidDataType(data_type)),
+        },
+    )
+}
 
-    if *offset + size > buf.len() {
-        return Err(Error::InvalidOffset);
-    }
+/// Read any integer type, returns its type along with its bits.
+#[inline]
+fn read_integer(buf: Data<'_>, offset: &mut usize) -> Result<(u8, u128), Error> {
+    decode_value(
+        buf.bytes,
+        offset,
+        |data_type, size, offset| match data_type {
+            DATA_TYPE_UINT16 | DATA_TYPE_UINT32 | DATA_TYPE_INT32 | DATA_TYPE_UINT64
+            | DATA_TYPE_UINT128 => {
+                bytes_to_integer(data_type, read_bytes(buf.bytes, offset, size)?)
+            }
+            _ => Err(Error::InvalidDataType(data_type)),
+        },
+    )
+}
 
-    let mut value = 0;
-    for pos in *offset..*offset + size {
-        let ch = buf[pos] as usize;
+/// Convert the bits of an integer to `T`, failing if the value doesn't fit.
+#[inline]
+fn integer_into<T: TryFrom<i128> + TryFrom<u128>>(
+    (data_type, bits): (u8, u128),
+) -> Result<T, Error> {
+    let value = if data_type == DATA_TYPE_INT32 {
+        T::try_from(bits as u32 as i32 as i128).ok()
+    } else {
+        T::try_from(bits).ok()
+    };
 
-        value = value << 8 | ch;
-    }
+    value.ok_or(Error::Overflow)
+}
 
-    *offset += size;
+pub(crate) fn read_u16(buf: Data<'_>, offset: &mut usize) -> Result<u16, Error> {
+    integer_into(read_integer(buf, offset)?)
+}
 
-    Ok(value)
+pub(crate) fn read_u32(buf: Data<'_>, offset: &mut usize) -> Result<u32, Error> {
+    integer_into(read_integer(buf, offset)?)
 }
 
-pub(crate) fn read_str_array<'a>(buf: &'a [u8], offset: &mut usize) -> Result<Vec<&'a str>, Error> {
-    let (data_type, size) = read_control(buf, offset)?;
-    match data_type {
-        DATA_TYPE_SLICE => {
-            let mut array = Vec::with_capacity(size);
-            for _ in 0..size {
-                array.push(read_str(buf, offset)?);
-            }
-            Ok(array)
-        }
-        DATA_TYPE_POINTER => {
-            let offset = &mut read_pointer(buf, offset, size)?;
-            let (data_type, size) = read_control(buf, offset)?;
-            match data_type {
-                DATA_TYPE_SLICE => {
-                    let mut array = Vec::with_capacity(size);
-                    for _ in 0..size {
-                        array.push(read_str(buf, offset)?);
-                    }
-                    Ok(array)
-                }
-                _ => Err(Error::InvalidDataType(data_type)),
-            }
-        }
-        _ => Err(Error::InvalidDataType(data_type)),
-    }
+pub(crate) fn read_u64(buf: Data<'_>, offset: &mut usize) -> Result<u64, Error> {
+    integer_into(read_integer(buf, offset)?)
+}
+
+pub(crate) fn read_usize(buf: Data<'_>, offset: &mut usize) -> Result<usize, Error> {
+    integer_into(read_integer(buf, offset)?)
 }
 
 pub(crate) fn read_map<'a>(
-    buf: &'a [u8],
+    buf: Data<'a>,
     offset: &mut usize,
 ) -> Result<Vec<(&'a str, &'a str)>, Error> {
-    let (data_type, size) = read_control(buf, offset)?;
+    let (data_type, size) = read_control(buf.bytes, offset)?;
 
     match data_type {
         DATA_TYPE_MAP => {
@@ -251,8 +484,8 @@ pub(crate) fn read_map<'a>(
             Ok(map)
         }
         DATA_TYPE_POINTER => {
-            let offset = &mut read_pointer(buf, offset, size)?;
-            let (data_type, size) = read_control(buf, offset)?;
+            let offset = &mut read_pointer(buf.bytes, offset, size)?;
+            let (data_type, size) = read_control(buf.bytes, offset)?;
             match data_type {
                 DATA_TYPE_MAP => {
                     let mut map = Vec::with_capacity(size);
@@ -269,7 +502,11 @@ pub(crate) fn read_map<'a>(
 }
 
 #[inline]
-fn read_bytes<'a>(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<&'a [u8], Error> {
+pub(crate) fn read_bytes<'a>(
+    buf: &'a [u8],
+    offset: &mut usize,
+    size: usize,
+) -> Result<&'a [u8], Error> {
     let new_offset = *offset + size;
     if new_offset > buf.len() {
         return Err(Error::InvalidOffset);
@@ -289,17 +526,353 @@ pub(crate) fn bytes_to_usize(buf: &[u8]) -> usize {
     value
 }
 
+/// Read the key of a map entry. Keys are matched on their bytes, only an
+/// unknown one is checked to be UTF-8 for its error. The key a pointer
+/// points to is matched once per reader, its cache has it after.
 #[inline]
-pub(crate) fn bytes_to_usize_with_prefix(prefix: usize, buf: &[u8]) -> usize {
-    match buf.len() {
-        0..=8 => {
-            let mut value = prefix;
-            for &b in buf {
-                value = value << 8 | b as usize
+pub(crate) fn read_key(buf: Data<'_>, offset: &mut usize) -> Result<Key, Error> {
+    let (data_type, size) = read_control(buf.bytes, offset)?;
+    match data_type {
+        DATA_TYPE_STRING => key_from_bytes(read_bytes(buf.bytes, offset, size)?),
+        DATA_TYPE_POINTER => {
+            let target = read_pointer(buf.bytes, offset, size)?;
+            if let Some(key) = buf.keys.and_then(|keys| keys.get(target)) {
+                return Ok(key);
+            }
+
+            let offset = &mut { target };
+            let key = match read_control(buf.bytes, offset)? {
+                (DATA_TYPE_STRING, size) => key_from_bytes(read_bytes(buf.bytes, offset, size)?)?,
+                (data_type, _) => return Err(Error::InvalidDataType(data_type)),
+            };
+            if let Some(keys) = buf.keys {
+                keys.insert(target, key);
             }
 
-            value
+            Ok(key)
         }
-        _ => 0,
+        _ => Err(Error::InvalidDataType(data_type)),
+    }
+}
+
+#[inline]
+fn key_from_bytes(name: &[u8]) -> Result<Key, Error> {
+    match Key::from_bytes(name) {
+        Some(key) => Ok(key),
+        None => Err(Error::UnknownField(bytes_to_str(name)?.to_string())),
+    }
+}
+
+#[inline]
+pub(crate) fn bytes_to_str(buf: &[u8]) -> Result<&str, Error> {
+    #[cfg(feature = "unsafe-str")]
+    return Ok(unsafe { std::str::from_utf8_unchecked(buf) });
+
+    #[cfg(not(feature = "unsafe-str"))]
+    std::str::from_utf8(buf).map_err(Error::InvalidUtf8)
+}
+
+#[inline]
+pub(crate) fn bytes_to_f64(buf: &[u8]) -> Result<f64, Error> {
+    let bytes: [u8; 8] = buf
+        .try_into()
+        .map_err(|_| Error::InvalidDataType(DATA_TYPE_FLOAT64))?;
+    Ok(f64::from_be_bytes(bytes))
+}
+
+#[inline]
+pub(crate) fn bytes_to_f32(buf: &[u8]) -> Result<f32, Error> {
+    let bytes: [u8; 4] = buf
+        .try_into()
+        .map_err(|_| Error::InvalidDataType(DATA_TYPE_FLOAT32))?;
+    Ok(f32::from_be_bytes(bytes))
+}
+
+/// Big endian bits of an integer, `buf` can't be larger than the type.
+#[inline]
+pub(crate) fn bytes_to_integer(data_type: u8, buf: &[u8]) -> Result<(u8, u128), Error> {
+    let max_size = match data_type {
+        DATA_TYPE_UINT16 => 2,
+        DATA_TYPE_UINT32 | DATA_TYPE_INT32 => 4,
+        DATA_TYPE_UINT64 => 8,
+        _ => 16,
+    };
+    if buf.len() > max_size {
+        return Err(Error::InvalidDataType(data_type));
+    }
+
+    let mut value = 0u128;
+    for &b in buf {
+        value = value << 8 | b as u128
+    }
+
+    Ok((data_type, value))
+}
+
+impl<'a> Decoder<'a> for bool {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        read_bool(buf, offset)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+macro_rules! integer_decoders {
+    ($($ty:ty),*) => {
+        $(
+            /// Any integer type of the database that fits.
+            impl<'a> Decoder<'a> for $ty {
+                fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+                    integer_into(read_integer(buf, offset)?)
+                }
+
+                fn decode_with_size(
+                    _buf: Data<'a>,
+                    _offset: &mut usize,
+                    _size: usize,
+                ) -> Result<Self, Error> {
+                    Err(Error::InvalidDataType(DATA_TYPE_MAP))
+                }
+            }
+        )*
+    };
+}
+
+integer_decoders!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl<'a> Decoder<'a> for f64 {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        read_f64(buf, offset)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+impl<'a> Decoder<'a> for f32 {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        decode_value(
+            buf.bytes,
+            offset,
+            |data_type, size, offset| match data_type {
+                DATA_TYPE_FLOAT32 => bytes_to_f32(read_bytes(buf.bytes, offset, size)?),
+                _ => Err(Error::InvalidDataType(data_type)),
+            },
+        )
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+impl<'a> Decoder<'a> for &'a str {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        read_str(buf, offset)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+impl<'a> Decoder<'a> for &'a [u8] {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        decode_value(
+            buf.bytes,
+            offset,
+            |data_type, size, offset| match data_type {
+                DATA_TYPE_BYTES => read_bytes(buf.bytes, offset, size),
+                _ => Err(Error::InvalidDataType(data_type)),
+            },
+        )
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+/// Always `Some`, for fields that may be missing from a record.
+impl<'a, T: Decoder<'a>> Decoder<'a> for Option<T> {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        T::decode(buf, offset).map(Some)
+    }
+
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        T::decode_with_size(buf, offset, size).map(Some)
+    }
+
+    fn decode_into(&mut self, buf: Data<'a>, offset: &mut usize, size: usize) -> Result<(), Error> {
+        match self {
+            Some(value) => value.decode_into(buf, offset, size),
+            None => {
+                *self = Some(T::decode_with_size(buf, offset, size)?);
+                Ok(())
+            }
+        }
+    }
+
+    fn decode_in_place(&mut self, buf: Data<'a>, offset: &mut usize) -> Result<(), Error> {
+        match self {
+            Some(value) => value.decode_in_place(buf, offset),
+            None => {
+                *self = Some(T::decode(buf, offset)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a, T: Decoder<'a>> Decoder<'a> for Vec<T> {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        let mut array = Vec::new();
+        array.decode_in_place(buf, offset)?;
+        Ok(array)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+
+    /// Clear the array and decode the elements into it, it doesn't
+    /// allocate once large enough.
+    fn decode_in_place(&mut self, buf: Data<'a>, offset: &mut usize) -> Result<(), Error> {
+        self.clear();
+        decode_value(
+            buf.bytes,
+            offset,
+            |data_type, size, offset| match data_type {
+                DATA_TYPE_SLICE => {
+                    for _ in 0..size {
+                        self.push(T::decode(buf, offset)?);
+                    }
+                    Ok(())
+                }
+                _ => Err(Error::InvalidDataType(data_type)),
+            },
+        )
+    }
+}
+
+impl<'a, T: Decoder<'a>, S: BuildHasher + Default> Decoder<'a> for HashMap<&'a str, T, S> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut map = HashMap::with_capacity_and_hasher(size, S::default());
+        for _ in 0..size {
+            map.insert(read_str(buf, offset)?, T::decode(buf, offset)?);
+        }
+
+        Ok(map)
+    }
+}
+
+impl<'a, T: Decoder<'a>> Decoder<'a> for BTreeMap<&'a str, T> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut map = BTreeMap::new();
+        for _ in 0..size {
+            map.insert(read_str(buf, offset)?, T::decode(buf, offset)?);
+        }
+
+        Ok(map)
+    }
+}
+
+macro_rules! tuple_decoders {
+    ($(($($ty:ident),+)),*) => {
+        $(
+            /// An array of exactly as many elements.
+            impl<'a, $($ty: Decoder<'a>),+> Decoder<'a> for ($($ty,)+) {
+                fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+                    decode_value(buf.bytes, offset, |data_type, size, offset| {
+                        const LEN: usize = [$(stringify!($ty)),+].len();
+                        match data_type {
+                            DATA_TYPE_SLICE if size == LEN => {
+                                Ok(($($ty::decode(buf, offset)?,)+))
+                            }
+                            DATA_TYPE_SLICE => Err(Error::InvalidArrayLength(size)),
+                            _ => Err(Error::InvalidDataType(data_type)),
+                        }
+                    })
+                }
+
+                fn decode_with_size(
+                    _buf: Data<'a>,
+                    _offset: &mut usize,
+                    _size: usize,
+                ) -> Result<Self, Error> {
+                    Err(Error::InvalidDataType(DATA_TYPE_MAP))
+                }
+            }
+        )*
+    };
+}
+
+tuple_decoders!((A), (A, B), (A, B, C), (A, B, C, D));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_sizes() {
+        for (buf, want) in [
+            (vec![0x5c], 28),
+            (vec![0x5d, 0x00], 29),
+            (vec![0x5d, 0xff], 284),
+            (vec![0x5e, 0x01, 0x02], 285 + 258),
+            (vec![0x5f, 0x00, 0x01, 0x02], 65_821 + 258),
+            // pointers use the size bits for themselves
+            (vec![0x3f, 0x00], 31),
+        ] {
+            let mut offset = 0;
+            let (_, size) = read_control(&buf, &mut offset).unwrap();
+            assert_eq!(size, want);
+        }
+    }
+
+    #[test]
+    fn integers() {
+        // int32 is sign extended
+        let buf = [0x04, 0x01, 0xf0, 0x00, 0x00, 0x00];
+        assert_eq!(
+            read_u64(Data::new(&buf), &mut 0).unwrap_err().to_string(),
+            "value overflows the decoded type"
+        );
+
+        // uint32 70000 doesn't fit an u16
+        let buf = [0xc3, 0x01, 0x11, 0x70];
+        assert_eq!(read_u32(Data::new(&buf), &mut 0).unwrap(), 70000);
+        assert!(matches!(
+            read_u16(Data::new(&buf), &mut 0),
+            Err(Error::Overflow)
+        ));
+
+        // integers wider than their type are invalid
+        let buf = [0xc5, 0x01, 0x00, 0x00, 0x00, 0x01];
+        assert!(matches!(
+            read_u32(Data::new(&buf), &mut 0),
+            Err(Error::InvalidDataType(DATA_TYPE_UINT32))
+        ));
+    }
+
+    #[test]
+    fn truncated_containers() {
+        for buf in [
+            // a map of one entry, and nothing after it
+            &[0xe1][..],
+            // the value of its key missing
+            &[0xe1, 0x41, 0x61],
+            // an array of one value
+            &[0x01, 0x04],
+            &[0xe1, 0x41, 0x61, 0x02, 0x04, 0xa0],
+        ] {
+            assert!(matches!(verify_values(buf), Err(Error::InvalidOffset)));
+        }
+
+        let buf = [0xe1, 0x41, 0x61, 0x01, 0x04, 0xa0];
+        assert!(verify_values(&buf).is_ok());
     }
 }
@@ -0,0 +1,390 @@
+//! Annotated disassembler for the data section, useful to inspect corrupt
+//! files and to check a writer emits canonical encodings.
+
+use std::fmt::{Display, Formatter};
+
+use crate::decode::{
+    read_bytes, read_control, read_pointer, type_name, DATA_TYPE_BOOL, DATA_TYPE_BYTES,
+    DATA_TYPE_FLOAT32, DATA_TYPE_FLOAT64, DATA_TYPE_INT32, DATA_TYPE_MAP, DATA_TYPE_POINTER,
+    DATA_TYPE_SLICE, DATA_TYPE_STRING, DATA_TYPE_UINT128, DATA_TYPE_UINT16, DATA_TYPE_UINT32,
+    DATA_TYPE_UINT64,
+};
+use crate::{Error, Reader};
+
+// The largest pointer a 1, 2 or 3 byte pointer can hold, anything below
+// it stored in a 4 byte pointer is not canonical.
+const MAX_SHORT_POINTER: usize = 526_336 + (1 << 27) - 1;
+
+/// An annotated value of the data section.
+#[derive(Debug)]
+pub struct Token {
+    /// Offset of the control byte in the data section.
+    pub offset: usize,
+    /// Nesting level, container members and pointer targets are one level deeper.
+    pub depth: usize,
+    /// Control byte, followed by the extended type byte and size bytes if any.
+    pub header: Vec<u8>,
+    pub data_type: u8,
+    /// Number of bytes of the payload, pointer bytes for pointers and
+    /// the number of entries for maps and arrays.
+    pub size: usize,
+    /// Target offset, only set for pointers.
+    pub target: Option<usize>,
+    /// Rendered value, empty for containers and pointers.
+    pub value: String,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>8}  {:<14}  {:<12}  {:>6}  {:indent$}",
+            self.offset,
+            hex(&self.header),
+            type_name(self.data_type),
+            self.size,
+            "",
+            indent = self.depth * 2
+        )?;
+
+        match self.target {
+            Some(target) => write!(f, "-> {target}"),
+            None => f.write_str(&self.value),
+        }
+    }
+}
+
+/// How the size of a value is encoded after its control byte.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SizeEncoding {
+    /// Sizes below 29 are stored in the control byte itself.
+    Inline,
+    /// Size bits are 29, the next byte holds `size - 29`.
+    OneByte,
+    /// Size bits are 30, the next two bytes hold `size - 285`.
+    TwoBytes,
+    /// Size bits are 31, the next three bytes hold `size - 65_821`.
+    ThreeBytes,
+    /// Pointers have no size, this is the number of pointer bytes.
+    Pointer(usize),
+}
+
+impl Display for SizeEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SizeEncoding::Inline => f.write_str("inline"),
+            SizeEncoding::OneByte => f.write_str("29+1"),
+            SizeEncoding::TwoBytes => f.write_str("30+2"),
+            SizeEncoding::ThreeBytes => f.write_str("31+3"),
+            SizeEncoding::Pointer(size) => write!(f, "ptr{size}"),
+        }
+    }
+}
+
+/// A value of the data section as it's encoded.
+#[derive(Debug)]
+pub struct Instruction {
+    pub token: Token,
+    pub encoding: SizeEncoding,
+    /// Set if the value can't be parsed, or is parsed but not canonically
+    /// encoded.
+    pub issue: Option<String>,
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<6}  {}", self.encoding, self.token)?;
+
+        match &self.issue {
+            Some(issue) => write!(f, "  ! {issue}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Walks the data section from offset 0, one value at a time. Container
+/// members are yielded right after their container, pointers are not
+/// followed.
+///
+/// Once a value can't be parsed, the disassembler skips a single byte and
+/// tries again, so a corrupt region shows up as a run of issues.
+pub struct Disassembler<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    // remaining values of the containers the walk is in, and whether the
+    // container is a map
+    stack: Vec<(usize, bool)>,
+}
+
+impl<S: AsRef<[u8]>> Reader<S> {
+    /// Disassemble the data section.
+    pub fn disassemble(&self) -> Disassembler<'_> {
+        Disassembler {
+            buf: self.data_section(),
+            offset: 0,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Iterator for Disassembler<'a> {
+    type Item = Instruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.buf.len() {
+            return None;
+        }
+
+        let start = self.offset;
+        let depth = self.stack.len();
+        let is_key = matches!(self.stack.last(), Some((remaining, true)) if remaining % 2 == 0);
+
+        match self.parse(depth, is_key) {
+            Ok(instruction) => {
+                if let Some((remaining, _)) = self.stack.last_mut() {
+                    *remaining -= 1;
+                }
+
+                match instruction.token.data_type {
+                    DATA_TYPE_MAP if instruction.token.size > 0 => {
+                        self.stack.push((instruction.token.size * 2, true))
+                    }
+                    DATA_TYPE_SLICE if instruction.token.size > 0 => {
+                        self.stack.push((instruction.token.size, false))
+                    }
+                    _ => {}
+                }
+
+                while matches!(self.stack.last(), Some((0, _))) {
+                    self.stack.pop();
+                }
+
+                Some(instruction)
+            }
+            Err(err) => {
+                let control_byte = self.buf[start];
+                self.offset = start + 1;
+                self.stack.clear();
+
+                Some(Instruction {
+                    token: Token {
+                        offset: start,
+                        depth,
+                        header: vec![control_byte],
+                        data_type: control_byte >> 5,
+                        size: 0,
+                        target: None,
+                        value: String::new(),
+                    },
+                    encoding: SizeEncoding::Inline,
+                    issue: Some(format!("unparseable, {err}")),
+                })
+            }
+        }
+    }
+}
+
+impl<'a> Disassembler<'a> {
+    fn parse(&mut self, depth: usize, is_key: bool) -> Result<Instruction, Error> {
+        let buf = self.buf;
+        let start = self.offset;
+        let mut offset = start;
+        let (data_type, size) = read_control(buf, &mut offset)?;
+        let mut token = Token {
+            offset: start,
+            depth,
+            header: buf[start..offset].to_vec(),
+            data_type,
+            size,
+            target: None,
+            value: String::new(),
+        };
+        let mut encoding = match buf[start] & 0x1f {
+            29 => SizeEncoding::OneByte,
+            30 => SizeEncoding::TwoBytes,
+            31 => SizeEncoding::ThreeBytes,
+            _ => SizeEncoding::Inline,
+        };
+        let mut issue = None;
+
+        if is_key && data_type != DATA_TYPE_STRING && data_type != DATA_TYPE_POINTER {
+            issue = Some("map key is not a string".to_string());
+        }
+
+        match data_type {
+            DATA_TYPE_POINTER => {
+                let target = read_pointer(buf, &mut offset, size)?;
+                token.header = buf[start..offset].to_vec();
+                token.size = token.header.len() - 1;
+                token.target = Some(target);
+                encoding = SizeEncoding::Pointer(token.size);
+
+                let mut target_offset = target;
+                match read_control(buf, &mut target_offset) {
+                    Err(_) => issue = Some("pointer target is not a value".to_string()),
+                    Ok((DATA_TYPE_POINTER, _)) => issue = Some("pointer to pointer".to_string()),
+                    Ok((target_type, _)) if is_key && target_type != DATA_TYPE_STRING => {
+                        issue = Some("map key is not a string".to_string())
+                    }
+                    Ok(_) if token.size == 4 && target <= MAX_SHORT_POINTER => {
+                        issue = Some("pointer could use fewer bytes".to_string())
+                    }
+                    Ok(_) => {}
+                }
+            }
+            DATA_TYPE_MAP | DATA_TYPE_SLICE => {}
+            _ => {
+                let payload = offset;
+                token.value = render_scalar(buf, &mut offset, data_type, size)?;
+
+                let max_size = match data_type {
+                    DATA_TYPE_UINT16 => 2,
+                    DATA_TYPE_UINT32 | DATA_TYPE_INT32 => 4,
+                    DATA_TYPE_UINT64 => 8,
+                    DATA_TYPE_UINT128 => 16,
+                    DATA_TYPE_BOOL => 1,
+                    _ => usize::MAX,
+                };
+                if size > max_size {
+                    issue = Some(format!("size {size} is too large for the type"));
+                } else if data_type != DATA_TYPE_BOOL
+                    && max_size != usize::MAX
+                    && size > 0
+                    && buf[payload] == 0
+                {
+                    issue = Some("integer has leading zero bytes".to_string());
+                }
+            }
+        }
+
+        self.offset = offset;
+
+        Ok(Instruction {
+            token,
+            encoding,
+            issue,
+        })
+    }
+}
+
+pub(crate) fn render_scalar(
+    buf: &[u8],
+    offset: &mut usize,
+    data_type: u8,
+    size: usize,
+) -> Result<String, Error> {
+    let value = match data_type {
+        DATA_TYPE_BOOL => return Ok((size != 0).to_string()),
+        DATA_TYPE_STRING => {
+            let data = read_bytes(buf, offset, size)?;
+            format!("{:?}", String::from_utf8_lossy(data))
+        }
+        DATA_TYPE_BYTES => {
+            let data = read_bytes(buf, offset, size)?;
+            data.iter().map(|b| format!("{b:02x}")).collect()
+        }
+        DATA_TYPE_FLOAT64 => {
+            let data = read_bytes(buf, offset, size)?;
+            let data: [u8; 8] = data
+                .try_into()
+                .map_err(|_| Error::InvalidDataType(data_type))?;
+            f64::from_be_bytes(data).to_string()
+        }
+        DATA_TYPE_FLOAT32 => {
+            let data = read_bytes(buf, offset, size)?;
+            let data: [u8; 4] = data
+                .try_into()
+                .map_err(|_| Error::InvalidDataType(data_type))?;
+            f32::from_be_bytes(data).to_string()
+        }
+        DATA_TYPE_UINT16 | DATA_TYPE_UINT32 | DATA_TYPE_INT32 | DATA_TYPE_UINT64
+        | DATA_TYPE_UINT128 => {
+            let data = read_bytes(buf, offset, size)?;
+            if data.len() > 16 {
+                return Err(Error::InvalidDataType(data_type));
+            }
+
+            let value = data.iter().fold(0u128, |acc, b| acc << 8 | *b as u128);
+            if data_type == DATA_TYPE_INT32 {
+                (value as u32 as i32).to_string()
+            } else {
+                value.to_string()
+            }
+        }
+        _ => return Err(Error::InvalidDataType(data_type)),
+    };
+
+    Ok(value)
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassemble() {
+        let reader = Reader::open_file("testdata/GeoIP2-City-Test.mmdb").unwrap();
+        let instructions = reader.disassemble().collect::<Vec<_>>();
+
+        assert!(!instructions.is_empty());
+        assert!(instructions.iter().all(|i| i.issue.is_none()));
+        assert!(instructions
+            .iter()
+            .any(|i| i.token.depth == 0 && i.token.data_type == DATA_TYPE_MAP));
+
+        // pointers are not followed, so the walk only moves forward
+        assert!(instructions
+            .windows(2)
+            .all(|pair| pair[0].token.offset < pair[1].token.offset));
+    }
+
+    #[test]
+    fn corrupt() {
+        // a map of one entry, a key that's an uint16 and a truncated string
+        let buf = [0xe1, 0xa1, 0x01, 0x45, b'a'];
+        let instructions = Disassembler {
+            buf: &buf,
+            offset: 0,
+            stack: Vec::new(),
+        }
+        .collect::<Vec<_>>();
+
+        assert_eq!(instructions[0].token.data_type, DATA_TYPE_MAP);
+        assert!(instructions[0].issue.is_none());
+        assert_eq!(
+            instructions[1].issue.as_deref(),
+            Some("map key is not a string")
+        );
+        assert!(instructions[2]
+            .issue
+            .as_deref()
+            .unwrap()
+            .starts_with("unparseable"));
+    }
+
+    #[test]
+    fn zero_size_integer() {
+        // an empty uint16 last, and one followed by a false, whose control
+        // byte is 0
+        for buf in [&[0xa0][..], &[0xa0, 0x00, 0x07]] {
+            let instructions = Disassembler {
+                buf,
+                offset: 0,
+                stack: Vec::new(),
+            }
+            .collect::<Vec<_>>();
+
+            assert_eq!(instructions[0].token.data_type, DATA_TYPE_UINT16);
+            assert_eq!(instructions[0].token.value, "0");
+            assert!(instructions.iter().all(|i| i.issue.is_none()));
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Graphviz export of the search tree, handy to teach the format and to
+//! debug small custom databases.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::net::IpAddr;
+
+use crate::decode::{
+    read_bytes, read_control, read_pointer, DATA_TYPE_MAP, DATA_TYPE_POINTER, DATA_TYPE_SLICE,
+    DATA_TYPE_STRING,
+};
+use crate::disasm::render_scalar;
+use crate::{Error, Network, Reader};
+
+// Leaf labels are cut to this many characters.
+const MAX_SUMMARY_LEN: usize = 48;
+const MAX_SUMMARY_DEPTH: usize = 32;
+
+impl<S: AsRef<[u8]>> Reader<S> {
+    /// Export the search tree below `prefix` as a Graphviz DOT graph, at
+    /// most `max_depth` levels deep. Edges are labeled with the bit they
+    /// consume, leaves with the data offset and a summary of the record.
+    ///
+    /// In IPv6 databases the start of the IPv4 subtree is drawn as a double
+    /// circle, and the nodes aliasing it, like `::ffff:0:0/96`, are blue.
+    pub fn export_dot(&self, prefix: Network, max_depth: usize) -> Result<String, Error> {
+        let ipv6_tree = self.ip_v4_start != 0;
+        let (bits, ipv4, width, mut node) = match prefix.addr() {
+            IpAddr::V4(addr) => ((u32::from(addr) as u128) << 96, true, 32, self.ip_v4_start),
+            IpAddr::V6(addr) => {
+                if !ipv6_tree {
+                    return Err(Error::IPv4Only);
+                }
+
+                (u128::from(addr), false, 128, 0)
+            }
+        };
+
+        // the node whose left record is the IPv4 subtree, any other record
+        // pointing to the subtree is an alias. `::/96` can end early, then
+        // there's no subtree to alias.
+        let mut ipv4_parent = 0;
+        let ipv4_subtree = ipv6_tree && self.ip_v4_start < self.node_count;
+        if ipv4_subtree {
+            for _ in 0..95 {
+                if ipv4_parent >= self.node_count {
+                    break;
+                }
+                ipv4_parent = self.read_node(ipv4_parent, 0);
+            }
+        }
+
+        let mut depth = 0;
+        while depth < prefix.prefix_len() as usize && node < self.node_count {
+            node = self.read_node(node, bit_at(bits, depth));
+            depth += 1;
+        }
+
+        let mut out = String::new();
+        out.push_str("digraph mmdb {\n");
+        out.push_str("  node [shape=circle, fontname=monospace];\n");
+        out.push_str("  edge [fontname=monospace];\n");
+
+        if node >= self.node_count {
+            self.write_leaf(&mut out, node, Network::from_bits(bits, depth as u8, ipv4));
+            out.push_str("}\n");
+            return Ok(out);
+        }
+
+        let mut nodes = HashSet::new();
+        let mut leaves = HashSet::new();
+        let mut stack = vec![(node, bits, depth)];
+        while let Some((node, bits, depth)) = stack.pop() {
+            if !nodes.insert(node) {
+                continue;
+            }
+
+            let network = Network::from_bits(bits, depth as u8, ipv4);
+            let records = [self.read_node(node, 0), self.read_node(node, 1)];
+            let alias = ipv4_subtree && node != ipv4_parent && records.contains(&self.ip_v4_start);
+
+            let mut attrs = format!("label=\"{node}\\n{network}");
+            if ipv4_subtree && node == self.ip_v4_start {
+                attrs.push_str("\\nIPv4 start\", shape=doublecircle");
+            } else {
+                attrs.push('"');
+            }
+            if alias {
+                attrs.push_str(", color=blue, fontcolor=blue");
+            }
+            if depth - prefix.prefix_len() as usize >= max_depth || depth >= width {
+                writeln!(out, "  {} [{attrs}, style=dashed];", self.node_name(node)).unwrap();
+                continue;
+            }
+            writeln!(out, "  {} [{attrs}];", self.node_name(node)).unwrap();
+
+            for (bit, record) in records.into_iter().enumerate() {
+                let child = if record == self.node_count {
+                    let name = format!("e{node}_{bit}");
+                    writeln!(out, "  {name} [shape=point];").unwrap();
+                    name
+                } else {
+                    self.node_name(record)
+                };
+
+                let mut edge = format!("label=\"{bit}\"");
+                if alias && record == self.ip_v4_start {
+                    edge = format!("label=\"{bit} alias\", style=dashed, color=blue");
+                }
+                writeln!(out, "  {} -> {child} [{edge}];", self.node_name(node)).unwrap();
+
+                let child_bits = bits | (bit as u128) << (127 - depth);
+                if record > self.node_count {
+                    if leaves.insert(record) {
+                        let network = Network::from_bits(child_bits, depth as u8 + 1, ipv4);
+                        self.write_leaf(&mut out, record, network);
+                    }
+                } else if record < self.node_count {
+                    stack.push((record, child_bits, depth + 1));
+                }
+            }
+        }
+
+        out.push_str("}\n");
+
+        Ok(out)
+    }
+
+    fn node_name(&self, record: usize) -> String {
+        if record > self.node_count {
+            format!("d{record}")
+        } else {
+            format!("n{record}")
+        }
+    }
+
+    fn write_leaf(&self, out: &mut String, record: usize, network: Network) {
+        if record == self.node_count {
+            writeln!(out, "  n{record} [shape=point, xlabel=\"{network}\"];").unwrap();
+            return;
+        }
+
+        let label = match self.resolve_data_pointer(record) {
+            Ok(offset) => {
+                let summary = summarize(self.data_section(), offset)
+                    .unwrap_or_else(|err| format!("invalid record, {err}"));
+                format!("offset {offset}\\n{}", escape(&summary))
+            }
+            Err(err) => escape(&err.to_string()),
+        };
+
+        writeln!(out, "  d{record} [shape=box, label=\"{label}\"];").unwrap();
+    }
+}
+
+#[inline]
+fn bit_at(bits: u128, depth: usize) -> usize {
+    (bits >> (127 - depth)) as usize & 1
+}
+
+/// One line summary of a record, the top level keys with their value, or
+/// the first string nested in it for maps and arrays.
+fn summarize(buf: &[u8], offset: usize) -> Result<String, Error> {
+    let start = offset;
+    let mut offset = offset;
+    let (data_type, size) = read_control(buf, &mut offset)?;
+    if data_type != DATA_TYPE_MAP {
+        return short(buf, &mut start.clone(), false, MAX_SUMMARY_DEPTH);
+    }
+
+    let mut summary = String::new();
+    for i in 0..size {
+        let key = short(buf, &mut offset, false, MAX_SUMMARY_DEPTH)?;
+        let value = short(buf, &mut offset, false, MAX_SUMMARY_DEPTH)?;
+
+        if i > 0 {
+            summary.push(' ');
+        }
+        summary.push_str(&key);
+        summary.push('=');
+        summary.push_str(&value);
+
+        if summary.chars().count() > MAX_SUMMARY_LEN {
+            let mut summary = summary.chars().take(MAX_SUMMARY_LEN).collect::<String>();
+            summary.push('…');
+            return Ok(summary);
+        }
+    }
+
+    Ok(summary)
+}
+
+// Render the value at `offset` shortly, inside containers only strings are
+// kept. `budget` bounds the recursion in case the data section is corrupt.
+fn short(buf: &[u8], offset: &mut usize, nested: bool, budget: usize) -> Result<String, Error> {
+    let budget = budget.checked_sub(1).ok_or(Error::InvalidOffset)?;
+    let (data_type, size) = read_control(buf, offset)?;
+
+    match data_type {
+        DATA_TYPE_POINTER => {
+            let mut target = read_pointer(buf, offset, size)?;
+            short(buf, &mut target, nested, budget)
+        }
+        DATA_TYPE_STRING => Ok(String::from_utf8_lossy(read_bytes(buf, offset, size)?).into()),
+        DATA_TYPE_MAP | DATA_TYPE_SLICE => {
+            let values = if data_type == DATA_TYPE_MAP {
+                size * 2
+            } else {
+                size
+            };
+
+            let mut found = None;
+            for i in 0..values {
+                let value = short(buf, offset, true, budget)?;
+                let is_key = data_type == DATA_TYPE_MAP && i % 2 == 0;
+                if found.is_none() && !is_key && !value.is_empty() {
+                    found = Some(value);
+                }
+            }
+
+            Ok(found.unwrap_or_default())
+        }
+        _ => {
+            let value = render_scalar(buf, offset, data_type, size)?;
+            Ok(if nested { String::new() } else { value })
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_dot() {
+        let reader = Reader::open_file("testdata/GeoIP2-City-Test.mmdb").unwrap();
+
+        let dot = reader
+            .export_dot("81.2.69.0/24".parse().unwrap(), 4)
+            .unwrap();
+        assert!(dot.starts_with("digraph mmdb {"));
+        assert!(dot.contains("81.2.69.0/24"));
+        assert!(dot.contains("city=London"));
+        // nodes at the max depth are not expanded
+        assert!(dot.contains("81.2.69.128/28\", style=dashed"));
+        assert!(!dot.contains("81.2.69.128/29"));
+
+        let dot = reader
+            .export_dot("::ffff:0:0/95".parse().unwrap(), 2)
+            .unwrap();
+        assert!(dot.contains("IPv4 start"));
+        assert!(dot.contains("alias"));
+
+        // the network is a single leaf
+        let dot = reader
+            .export_dot("81.2.69.142/32".parse().unwrap(), 4)
+            .unwrap();
+        assert!(dot.contains("shape=box"));
+        assert!(!dot.contains("->"));
+    }
+
+    #[test]
+    fn shallow_ipv6_tree() {
+        // a single node, `::/96` ends at depth 1
+        let reader = Reader::open_file("testdata/Synthetic-Shallow-IPv6-Test.mmdb").unwrap();
+
+        let dot = reader.export_dot("::/0".parse().unwrap(), 8).unwrap();
+        assert!(dot.contains("half=0"));
+        assert!(dot.contains("half=1"));
+        assert!(!dot.contains("IPv4 start"));
+        assert!(!dot.contains("alias"));
+
+        // IPv4 addresses are all in `::/1`
+        let dot = reader.export_dot("0.0.0.0/0".parse().unwrap(), 8).unwrap();
+        assert!(dot.contains("half=0"));
+        assert!(!dot.contains("->"));
+    }
+}
@@ -11,11 +11,15 @@ pub enum Error {
     MetadataNotFound,
     CorruptSearchTree,
     IPv4Only,
+    InvalidNetwork,
+    Overflow,
+    InvalidArrayLength(usize),
     Open(std::io::Error),
     UnknownField(String),
-
-    #[cfg(not(feature = "unsafe-str"))]
+    UnknownLanguage(String),
+    InvalidSchema(String),
     InvalidUtf8(std::str::Utf8Error),
+    Write(std::io::Error),
 }
 
 impl From<std::io::Error> for Error {
@@ -37,10 +41,17 @@ impl Display for Error {
             Error::MetadataNotFound => fmt.write_str("metadata is not found")?,
             Error::CorruptSearchTree => fmt.write_str("search tree is corrupt")?,
             Error::IPv4Only => fmt.write_str("database is IPv4 only")?,
+            Error::InvalidNetwork => fmt.write_str("invalid network")?,
+            Error::Overflow => fmt.write_str("value overflows the decoded type")?,
+            Error::InvalidArrayLength(len) => write!(fmt, "invalid array length {len}")?,
             Error::Open(err) => write!(fmt, "open file failed, {err}")?,
             Error::UnknownField(field) => write!(fmt, "unknown field {field}")?,
-            #[cfg(not(feature = "unsafe-str"))]
+            Error::UnknownLanguage(language) => {
+                write!(fmt, "language {language} is not in the database")?
+            }
+            Error::InvalidSchema(reason) => write!(fmt, "invalid schema, {reason}")?,
             Error::InvalidUtf8(err) => Display::fmt(err, fmt)?,
+            Error::Write(err) => write!(fmt, "write failed, {err}")?,
         }
 
         Ok(())
@@ -0,0 +1,276 @@
+//! Step by step trace of a single lookup, mostly useful to understand why an
+//! address resolves to an unexpected record.
+
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+
+use crate::decode::{
+    read_control, read_pointer, DATA_TYPE_MAP, DATA_TYPE_POINTER, DATA_TYPE_SLICE,
+};
+use crate::disasm::{hex, render_scalar, Token};
+use crate::{Error, Reader};
+
+// Corrupt data section could point back to a container it's nested in.
+const MAX_DEPTH: usize = 32;
+
+/// A node visited while walking the search tree.
+#[derive(Debug)]
+pub struct Step {
+    /// Index of the address bit consumed at this node.
+    pub bit_index: usize,
+    /// The consumed bit, 0 follows the left record and 1 follows the right one.
+    pub bit: u8,
+    pub node: usize,
+    /// Raw bytes of the node, both records included.
+    pub bytes: Vec<u8>,
+    pub left: usize,
+    pub right: usize,
+}
+
+/// Where the search tree walk ended.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The record equals the node count, the address is not in the database.
+    NotFound,
+    /// The record points into the data section.
+    Data { record: usize, offset: usize },
+}
+
+/// The trace of a lookup, see [`Reader::explain`].
+#[derive(Debug)]
+pub struct Explain {
+    pub address: IpAddr,
+    pub record_size: usize,
+    pub node_count: usize,
+    pub ip_v4_start: usize,
+    /// The node the walk started from, `ip_v4_start` for IPv4 addresses.
+    pub start_node: usize,
+    pub steps: Vec<Step>,
+    /// Number of bits consumed before the walk left the tree.
+    pub prefix_len: usize,
+    pub outcome: Outcome,
+    /// The decoded record, empty if the address is not found.
+    pub tokens: Vec<Token>,
+}
+
+impl<S: AsRef<[u8]>> Reader<S> {
+    /// Trace the lookup of `addr`, recording every node visited and every
+    /// value decoded, pointers followed included.
+    pub fn explain(&self, addr: IpAddr) -> Result<Explain, Error> {
+        let (ip, start_node) = match addr {
+            IpAddr::V4(addr) => (addr.octets().to_vec(), self.ip_v4_start),
+            IpAddr::V6(addr) => {
+                if self.ip_v4_start == 0 {
+                    return Err(Error::IPv4Only);
+                }
+
+                (addr.octets().to_vec(), 0)
+            }
+        };
+
+        let buf = self.data.as_ref();
+        let mut steps = Vec::new();
+        let mut node = start_node;
+        let mut prefix_len = 0;
+        for i in 0..ip.len() * 8 {
+            if node >= self.node_count {
+                break;
+            }
+
+            let bit = 1 & (ip[i >> 3] >> (7 - (i % 8)));
+            let base = node * self.node_offset_multi;
+            let left = self.read_node(node, 0);
+            let right = self.read_node(node, 1);
+            steps.push(Step {
+                bit_index: i,
+                bit,
+                node,
+                bytes: buf[base..base + self.node_offset_multi].to_vec(),
+                left,
+                right,
+            });
+
+            node = if bit == 0 { left } else { right };
+            prefix_len = i + 1;
+        }
+
+        let mut tokens = Vec::new();
+        let outcome = if node == self.node_count {
+            Outcome::NotFound
+        } else if node > self.node_count {
+            let offset = self.resolve_data_pointer(node)?;
+            annotate(self.data_section(), &mut offset.clone(), 0, &mut tokens)?;
+
+            Outcome::Data {
+                record: node,
+                offset,
+            }
+        } else {
+            return Err(Error::InvalidNode);
+        };
+
+        Ok(Explain {
+            address: addr,
+            record_size: self.record_size,
+            node_count: self.node_count,
+            ip_v4_start: self.ip_v4_start,
+            start_node,
+            steps,
+            prefix_len,
+            outcome,
+            tokens,
+        })
+    }
+}
+
+fn annotate(
+    buf: &[u8],
+    offset: &mut usize,
+    depth: usize,
+    tokens: &mut Vec<Token>,
+) -> Result<(), Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::InvalidOffset);
+    }
+
+    let start = *offset;
+    let (data_type, size) = read_control(buf, offset)?;
+    let mut token = Token {
+        offset: start,
+        depth,
+        header: buf[start..*offset].to_vec(),
+        data_type,
+        size,
+        target: None,
+        value: String::new(),
+    };
+
+    match data_type {
+        DATA_TYPE_POINTER => {
+            let target = read_pointer(buf, offset, size)?;
+            token.header = buf[start..*offset].to_vec();
+            // the size bits of a pointer are part of its value, report
+            // the number of pointer bytes instead
+            token.size = token.header.len() - 1;
+            token.target = Some(target);
+            tokens.push(token);
+
+            annotate(buf, &mut target.clone(), depth + 1, tokens)?;
+        }
+        DATA_TYPE_MAP => {
+            tokens.push(token);
+            for _ in 0..size {
+                annotate(buf, offset, depth + 1, tokens)?;
+                annotate(buf, offset, depth + 1, tokens)?;
+            }
+        }
+        DATA_TYPE_SLICE => {
+            tokens.push(token);
+            for _ in 0..size {
+                annotate(buf, offset, depth + 1, tokens)?;
+            }
+        }
+        _ => {
+            token.value = render_scalar(buf, offset, data_type, size)?;
+            tokens.push(token);
+        }
+    }
+
+    Ok(())
+}
+
+impl Display for Explain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "address      {}", self.address)?;
+        writeln!(
+            f,
+            "search tree  {} nodes, {} bit records, IPv4 subtree at node {}",
+            self.node_count, self.record_size, self.ip_v4_start
+        )?;
+        writeln!(f, "start node   {}", self.start_node)?;
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "{:>4}  {:>3}  {:>10}  {:<23}  {:>10}  {:>10}",
+            "bit", "val", "node", "bytes", "left", "right"
+        )?;
+        for step in &self.steps {
+            writeln!(
+                f,
+                "{:>4}  {:>3}  {:>10}  {:<23}  {:>10}{} {:>10}{}",
+                step.bit_index,
+                step.bit,
+                step.node,
+                hex(&step.bytes),
+                step.left,
+                if step.bit == 0 { "*" } else { " " },
+                step.right,
+                if step.bit == 1 { "*" } else { "" },
+            )?;
+        }
+        writeln!(f)?;
+
+        match &self.outcome {
+            Outcome::NotFound => {
+                return writeln!(
+                    f,
+                    "left the tree after {} bits, record {} is the empty record, address not found",
+                    self.prefix_len, self.node_count
+                );
+            }
+            Outcome::Data { record, offset } => writeln!(
+                f,
+                "left the tree after {} bits, record {} points to data offset {}",
+                self.prefix_len, record, offset
+            )?,
+        }
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "{:>8}  {:<14}  {:<12}  {:>6}  value",
+            "offset", "control", "type", "size"
+        )?;
+        for token in &self.tokens {
+            writeln!(f, "{token}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explain() {
+        let reader = Reader::open_file("testdata/GeoIP2-City-Test.mmdb").unwrap();
+        let addr = "81.2.69.142".parse().unwrap();
+        let explain = reader.explain(addr).unwrap();
+
+        assert_eq!(explain.start_node, reader.ip_v4_start);
+        assert_eq!(explain.steps.len(), explain.prefix_len);
+        assert!(matches!(explain.outcome, Outcome::Data { .. }));
+        assert_eq!(explain.tokens[0].data_type, DATA_TYPE_MAP);
+        assert!(explain
+            .tokens
+            .iter()
+            .any(|token| token.value == "\"Europe/London\""));
+
+        // the walk must agree with lookup
+        let pointer = reader.find_address_in_tree(&[81, 2, 69, 142]).unwrap();
+        assert_eq!(
+            explain.outcome,
+            Outcome::Data {
+                record: pointer,
+                offset: reader.resolve_data_pointer(pointer).unwrap()
+            }
+        );
+
+        let explain = reader.explain("10.0.0.1".parse().unwrap()).unwrap();
+        assert_eq!(explain.outcome, Outcome::NotFound);
+        assert!(explain.tokens.is_empty());
+    }
+}
@@ -0,0 +1,462 @@
+//! Records flattened to `(dotted_path, scalar)` pairs, for logs and metrics
+//! attributes.
+
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::slice;
+
+use crate::decode::{
+    read_control, read_pointer, read_str, skip_value, Data, Decoder, DATA_TYPE_MAP,
+    DATA_TYPE_POINTER, DATA_TYPE_SLICE, MAX_DEPTH,
+};
+use crate::{Error, Reader, Value};
+
+/// How the elements of an array are named in a path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndexFormat {
+    /// `subdivisions.0.iso_code`
+    #[default]
+    Dotted,
+    /// `subdivisions[0].iso_code`
+    Brackets,
+}
+
+/// Options of [`Value::flatten`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FlattenOptions<'o> {
+    /// Put in front of every path with a dot, e.g. `geo` for
+    /// `geo.country.iso_code`.
+    pub prefix: &'o str,
+    pub index_format: IndexFormat,
+    /// Replace every `names` map with a single `name`, in the first of
+    /// these languages available. Maps without any of them are left out.
+    pub languages: Option<&'o [&'o str]>,
+    /// Only keep the paths matching one of these globs, all of them if
+    /// empty. `*` matches any number of characters, dots included, and
+    /// globs don't include the prefix, e.g. `country.*`.
+    pub include: &'o [&'o str],
+    /// Leave out the paths matching any of these globs.
+    pub exclude: &'o [&'o str],
+}
+
+impl<'a> Value<'a> {
+    /// Iterate over the scalars of this value, maps and arrays excluded,
+    /// along with their dotted paths.
+    ///
+    /// ```
+    /// # use maxminddb::{FlattenOptions, Value};
+    /// let city = Value::Map(vec![(
+    ///     "country",
+    ///     Value::Map(vec![("iso_code", Value::String("US"))]),
+    /// )]);
+    /// let options = FlattenOptions {
+    ///     prefix: "geo",
+    ///     ..FlattenOptions::default()
+    /// };
+    /// let flat: Vec<_> = city.flatten(&options).collect();
+    /// assert_eq!(flat, [("geo.country.iso_code".to_string(), &Value::String("US"))]);
+    /// ```
+    pub fn flatten<'v>(&'v self, options: &FlattenOptions<'v>) -> Flatten<'v, 'a> {
+        Flatten {
+            path: Path::new(options),
+            stack: vec![Frame::Root(Some(self))],
+        }
+    }
+}
+
+/// The dotted path of the value being flattened, and whether the options
+/// keep it. Both [`Flatten`] and [`FlattenRecord`] name their values with
+/// it.
+struct Path<'o> {
+    options: FlattenOptions<'o>,
+    path: String,
+    // the length of the path of every container entered, with a trailing
+    // dot for maps
+    containers: Vec<usize>,
+}
+
+impl<'o> Path<'o> {
+    fn new(options: &FlattenOptions<'o>) -> Self {
+        Path {
+            options: *options,
+            path: String::from(options.prefix),
+            containers: Vec::new(),
+        }
+    }
+
+    // the value named by the path is a map, its entries are named next
+    fn enter_map(&mut self) {
+        if !self.path.is_empty() {
+            self.path.push('.');
+        }
+        self.containers.push(self.path.len());
+    }
+
+    fn enter_array(&mut self) {
+        self.containers.push(self.path.len());
+    }
+
+    fn leave(&mut self) {
+        self.containers.pop();
+    }
+
+    fn truncate(&mut self) {
+        if let Some(len) = self.containers.last() {
+            self.path.truncate(*len);
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        self.truncate();
+        self.path.push_str(key);
+    }
+
+    fn index(&mut self, index: usize) {
+        self.truncate();
+        let _ = match self.options.index_format {
+            IndexFormat::Dotted if self.path.is_empty() => write!(self.path, "{index}"),
+            IndexFormat::Dotted => write!(self.path, ".{index}"),
+            IndexFormat::Brackets => write!(self.path, "[{index}]"),
+        };
+    }
+
+    /// The name to find in a map under `key`, if a `names` map is to be
+    /// replaced with a single `name`.
+    fn names<T>(&self, key: &str) -> Option<BestName<'o, T>> {
+        match self.options.languages {
+            Some(languages) if key == "names" => Some(BestName {
+                languages,
+                name: None,
+            }),
+            _ => None,
+        }
+    }
+
+    // whether the globs keep the path, the prefix left out
+    fn kept(&self) -> bool {
+        let path = &self.path[self.options.prefix.len().min(self.path.len())..];
+        // the elements of a record that is an array
+        let path = path.strip_prefix('.').unwrap_or(path);
+
+        (self.options.include.is_empty()
+            || self.options.include.iter().any(|glob| matches(glob, path)))
+            && !self.options.exclude.iter().any(|glob| matches(glob, path))
+    }
+}
+
+/// The name of a `names` map in the first language available.
+struct BestName<'o, T> {
+    languages: &'o [&'o str],
+    // the index in the languages of the name
+    name: Option<(usize, T)>,
+}
+
+impl<T> BestName<'_, T> {
+    /// The index of `language` if its name would be better than the one
+    /// found so far.
+    fn wants(&self, language: &str) -> Option<usize> {
+        let index = self.languages.iter().position(|l| *l == language)?;
+        match self.name {
+            Some((best, _)) if best <= index => None,
+            _ => Some(index),
+        }
+    }
+
+    fn offer(&mut self, index: usize, name: T) {
+        self.name = Some((index, name));
+    }
+
+    fn name(self) -> Option<T> {
+        self.name.map(|(_, name)| name)
+    }
+}
+
+enum Frame<'v, 'a> {
+    Root(Option<&'v Value<'a>>),
+    Map(slice::Iter<'v, (&'a str, Value<'a>)>),
+    Array(std::iter::Enumerate<slice::Iter<'v, Value<'a>>>),
+}
+
+/// Iterator of [`Value::flatten`].
+pub struct Flatten<'v, 'a> {
+    path: Path<'v>,
+    stack: Vec<Frame<'v, 'a>>,
+}
+
+impl<'v, 'a> Iterator for Flatten<'v, 'a> {
+    type Item = (String, &'v Value<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let value = match self.stack.last_mut()? {
+                Frame::Root(root) => match root.take() {
+                    Some(root) => root,
+                    None => {
+                        self.stack.pop();
+                        continue;
+                    }
+                },
+                Frame::Map(entries) => {
+                    let Some((key, value)) = entries.next() else {
+                        self.stack.pop();
+                        self.path.leave();
+                        continue;
+                    };
+
+                    self.path.key(key);
+                    if let Value::Map(names) = value {
+                        if let Some(mut best) = self.path.names(key) {
+                            self.path.key("name");
+                            for (language, name) in names {
+                                if let (Some(index), Value::String(_)) =
+                                    (best.wants(language), name)
+                                {
+                                    best.offer(index, name);
+                                }
+                            }
+                            match best.name() {
+                                Some(name) if self.path.kept() => {
+                                    return Some((self.path.path.clone(), name))
+                                }
+                                _ => continue,
+                            }
+                        }
+                    }
+                    value
+                }
+                Frame::Array(elements) => {
+                    let Some((index, value)) = elements.next() else {
+                        self.stack.pop();
+                        self.path.leave();
+                        continue;
+                    };
+                    self.path.index(index);
+                    value
+                }
+            };
+
+            match value {
+                Value::Map(map) => {
+                    self.path.enter_map();
+                    self.stack.push(Frame::Map(map.iter()));
+                }
+                Value::Array(array) => {
+                    self.path.enter_array();
+                    self.stack.push(Frame::Array(array.iter().enumerate()));
+                }
+                scalar if self.path.kept() => return Some((self.path.path.clone(), scalar)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<S: AsRef<[u8]>> Reader<S> {
+    /// Like [`Value::flatten`] on the record of `addr`, without decoding
+    /// it first: the record is read as the iterator goes.
+    pub fn lookup_flatten<'a, 'o>(
+        &'a self,
+        addr: IpAddr,
+        options: &FlattenOptions<'o>,
+    ) -> Result<FlattenRecord<'a, 'o>, Error> {
+        let offset = self.find_value(addr)?;
+        Ok(FlattenRecord {
+            buf: self.data(),
+            path: Path::new(options),
+            stack: vec![Entries {
+                offset,
+                remaining: 1,
+                kind: Kind::Root,
+                inline: true,
+            }],
+        })
+    }
+}
+
+enum Kind {
+    Root,
+    Map,
+    // the index of the next element
+    Array(usize),
+}
+
+/// The values of a container left to flatten.
+struct Entries {
+    // of the next key or value
+    offset: usize,
+    remaining: usize,
+    kind: Kind,
+    // the container is where its parent's offset is, not behind a pointer
+    inline: bool,
+}
+
+/// Iterator of [`Reader::lookup_flatten`].
+pub struct FlattenRecord<'a, 'o> {
+    buf: Data<'a>,
+    path: Path<'o>,
+    stack: Vec<Entries>,
+}
+
+impl<'a> FlattenRecord<'a, '_> {
+    fn step(&mut self) -> Result<Option<(String, Value<'a>)>, Error> {
+        let FlattenRecord { buf, path, stack } = self;
+        let bytes = buf.bytes();
+        while let Some(entries) = stack.last_mut() {
+            if entries.remaining == 0 {
+                let (offset, inline) = (entries.offset, entries.inline);
+                stack.pop();
+                let Some(parent) = stack.last_mut() else {
+                    break;
+                };
+                if inline {
+                    parent.offset = offset;
+                }
+                path.leave();
+                continue;
+            }
+            entries.remaining -= 1;
+
+            let mut names = None;
+            match entries.kind {
+                Kind::Root => {}
+                Kind::Map => {
+                    let key = read_str(*buf, &mut entries.offset)?;
+                    path.key(key);
+                    names = path.names(key);
+                }
+                Kind::Array(ref mut index) => {
+                    path.index(*index);
+                    *index += 1;
+                }
+            }
+
+            // the contents of the value, behind a pointer or not
+            let start = entries.offset;
+            let mut contents = start;
+            let (mut data_type, mut size) = read_control(bytes, &mut contents)?;
+            let inline = data_type != DATA_TYPE_POINTER;
+            if !inline {
+                let target = read_pointer(bytes, &mut contents, size)?;
+                entries.offset = contents;
+                contents = target;
+                (data_type, size) = read_control(bytes, &mut contents)?;
+            }
+
+            match (data_type, names) {
+                (DATA_TYPE_MAP, Some(mut best)) => {
+                    path.key("name");
+                    for _ in 0..size {
+                        let language = read_str(*buf, &mut contents)?;
+                        match best.wants(language) {
+                            Some(index) => {
+                                if let Value::String(name) = Value::decode(*buf, &mut contents)? {
+                                    best.offer(index, name);
+                                }
+                            }
+                            None => skip_value(bytes, &mut contents)?,
+                        }
+                    }
+                    if inline {
+                        entries.offset = contents;
+                    }
+                    match best.name() {
+                        Some(name) if path.kept() => {
+                            return Ok(Some((path.path.clone(), Value::String(name))))
+                        }
+                        _ => continue,
+                    }
+                }
+                (DATA_TYPE_MAP | DATA_TYPE_SLICE, _) => {
+                    if stack.len() > MAX_DEPTH {
+                        return Err(Error::InvalidOffset);
+                    }
+                    let kind = match data_type {
+                        DATA_TYPE_MAP => {
+                            path.enter_map();
+                            Kind::Map
+                        }
+                        _ => {
+                            path.enter_array();
+                            Kind::Array(0)
+                        }
+                    };
+                    stack.push(Entries {
+                        offset: contents,
+                        remaining: size,
+                        kind,
+                        inline,
+                    });
+                }
+                _ => {
+                    let mut end = start;
+                    let value = Value::decode(*buf, &mut end)?;
+                    entries.offset = end;
+                    if path.kept() {
+                        return Ok(Some((path.path.clone(), value)));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for FlattenRecord<'a, '_> {
+    type Item = Result<(String, Value<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(flat) => flat.map(Ok),
+            Err(err) => {
+                // nothing after an error
+                self.stack.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+// whether `text` matches `glob`, where `*` matches any run of characters
+fn matches(glob: &str, text: &str) -> bool {
+    let Some((head, rest)) = glob.split_once('*') else {
+        return glob == text;
+    };
+    let Some(mut text) = text.strip_prefix(head) else {
+        return false;
+    };
+
+    // every part between stars is matched as early as possible, the last
+    // one at the end
+    let mut parts = rest.split('*').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return text.ends_with(part);
+        }
+        match text.find(part) {
+            Some(i) => text = &text[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn glob() {
+        assert!(matches("country.iso_code", "country.iso_code"));
+        assert!(!matches("country.iso_code", "country.iso_code2"));
+        assert!(matches("country.*", "country.names.en"));
+        assert!(!matches("country.*", "registered_country.iso_code"));
+        assert!(matches("*.iso_code", "subdivisions.0.iso_code"));
+        assert!(matches("*country*", "registered_country.iso_code"));
+        assert!(matches("*", ""));
+        assert!(matches("a*b*c", "abc"));
+        assert!(!matches("a*b*c", "acb"));
+        assert!(!matches("ab*ba", "aba"));
+    }
+}
@@ -0,0 +1,416 @@
+//! Records written straight to JSON, in the shape of the GeoIP2 web
+//! services.
+
+use std::io::Write;
+use std::net::IpAddr;
+
+use crate::decode::{read_control, DATA_TYPE_MAP};
+use crate::visit::{walk, Visitor, Walk};
+use crate::{Error, Network, Reader};
+
+/// Options of [`Reader::lookup_json_with`].
+#[derive(Clone, Copy, Debug)]
+pub struct JsonOptions<'f> {
+    /// Only write these fields, as dotted paths like `country.iso_code`.
+    /// A path writes everything under it, elements of arrays are matched
+    /// like the array itself, e.g. `subdivisions.iso_code`.
+    pub fields: Option<&'f [&'f str]>,
+    /// Add `ip_address` and `network` to `traits` like the GeoIP2 web
+    /// services do.
+    pub web_service: bool,
+}
+
+impl Default for JsonOptions<'_> {
+    fn default() -> Self {
+        JsonOptions {
+            fields: None,
+            web_service: true,
+        }
+    }
+}
+
+impl<S: AsRef<[u8]>> Reader<S> {
+    /// Write the record of `addr` to `out` as JSON, in the shape of the
+    /// GeoIP2 web services. Nothing is allocated in between. Records that
+    /// are not maps are written as they are, without traits or filtering.
+    pub fn lookup_json<W: Write>(&self, addr: IpAddr, out: &mut W) -> Result<(), Error> {
+        self.lookup_json_with(addr, &JsonOptions::default(), out)
+    }
+
+    /// Like [`Reader::lookup_json`] with `options`.
+    pub fn lookup_json_with<W: Write>(
+        &self,
+        addr: IpAddr,
+        options: &JsonOptions,
+        out: &mut W,
+    ) -> Result<(), Error> {
+        let start = self.find_value(addr)?;
+        let buf = self.data();
+
+        let is_map = matches!(
+            read_control(buf.bytes(), &mut start.clone())?,
+            (DATA_TYPE_MAP, _)
+        );
+        let traits = match options.web_service && is_map {
+            true => Some(Traits {
+                ip_address: addr,
+                network: self.network_of(addr)?,
+            }),
+            false => None,
+        };
+        let mut json = Json {
+            out,
+            fields: options.fields.filter(|_| is_map),
+            traits: traits.as_ref(),
+            wrote_traits: false,
+            keys: [""; MAX_DEPTH],
+            maps: 0,
+            depth: 0,
+            pending: None,
+            comma: false,
+        };
+
+        walk(buf, &mut start.clone(), &mut json)
+    }
+
+    /// The network of the tree holding `addr`.
+    fn network_of(&self, addr: IpAddr) -> Result<Network, Error> {
+        let (ip, bit_count, mut node) = match addr {
+            IpAddr::V4(addr) => (u128::from(addr.to_bits()) << 96, 32, self.ip_v4_start),
+            IpAddr::V6(addr) => (addr.to_bits(), 128, 0),
+        };
+
+        let mut prefix_len = 0;
+        while node < self.node_count && prefix_len < bit_count {
+            node = self.read_node(node, (ip >> (127 - prefix_len)) as usize & 1);
+            prefix_len += 1;
+        }
+
+        Network::new(addr, prefix_len)
+    }
+}
+
+struct Traits {
+    ip_address: IpAddr,
+    network: Network,
+}
+
+// keys deeper than this are only written if a field covers a shallower key
+const MAX_DEPTH: usize = 16;
+
+enum Allowed {
+    No,
+    // some fields under the path are allowed
+    Partially,
+    All,
+}
+
+enum Strip<'f> {
+    Mismatch,
+    // the path is under the field
+    Under,
+    // what is left of the field under the path, empty if it's the path
+    Rest(&'f str),
+}
+
+// match the keys of the path with the leading segments of `field`
+fn strip<'f>(path: &[&str], mut field: &'f str) -> Strip<'f> {
+    for (i, key) in path.iter().enumerate() {
+        if i > 0 && field.is_empty() {
+            return Strip::Under;
+        }
+
+        field = match field.split_once('.') {
+            Some((head, rest)) if head == *key => rest,
+            None if field == *key => "",
+            _ => return Strip::Mismatch,
+        };
+    }
+
+    Strip::Rest(field)
+}
+
+struct Json<'a, 'f, 't, W> {
+    out: W,
+    fields: Option<&'f [&'f str]>,
+    traits: Option<&'t Traits>,
+    wrote_traits: bool,
+    // the key of every map up to the value visited
+    keys: [&'a str; MAX_DEPTH],
+    maps: usize,
+    // maps and arrays
+    depth: usize,
+    // a key only written if its value is a map or an array, which some
+    // fields are under
+    pending: Option<&'a str>,
+    comma: bool,
+}
+
+impl<'a, W: Write> Visitor<'a> for Json<'a, '_, '_, W> {
+    fn visit_map_start(&mut self, _len: usize) -> Result<Walk, Error> {
+        self.start(b"{")?;
+        self.maps += 1;
+        if self.is_traits() {
+            self.wrote_traits = true;
+        }
+        Ok(Walk::Enter)
+    }
+
+    fn visit_key(&mut self, key: &'a str) -> Result<Walk, Error> {
+        if let Some(slot) = self.keys.get_mut(self.maps - 1) {
+            *slot = key;
+        }
+
+        match self.allowed() {
+            Allowed::No => return Ok(Walk::Skip),
+            Allowed::Partially => self.pending = Some(key),
+            Allowed::All => self.write_key(key)?,
+        }
+        Ok(Walk::Enter)
+    }
+
+    fn visit_map_end(&mut self) -> Result<(), Error> {
+        if let Some(traits) = self.traits {
+            if self.is_traits() {
+                self.write_address(traits)?;
+            } else if self.depth == 1 && !self.wrote_traits {
+                // the record has no traits, the address still has some
+                self.keys[0] = "traits";
+                if !matches!(self.allowed(), Allowed::No) {
+                    self.write_key("traits")?;
+                    self.start(b"{")?;
+                    self.maps += 1;
+                    self.write_address(traits)?;
+                    self.maps -= 1;
+                    self.end(b"}")?;
+                }
+            }
+        }
+
+        self.maps -= 1;
+        self.end(b"}")
+    }
+
+    fn visit_array_start(&mut self, _len: usize) -> Result<Walk, Error> {
+        self.start(b"[")?;
+        Ok(Walk::Enter)
+    }
+
+    fn visit_array_end(&mut self) -> Result<(), Error> {
+        self.end(b"]")
+    }
+
+    fn visit_str(&mut self, value: &'a str) -> Result<(), Error> {
+        if self.scalar()? {
+            self.write_str(value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_bytes(&mut self, value: &'a [u8]) -> Result<(), Error> {
+        if self.scalar()? {
+            self.write(b"[")?;
+            for (i, b) in value.iter().enumerate() {
+                let sep = if i > 0 { "," } else { "" };
+                write!(self.out, "{sep}{b}").map_err(Error::Write)?;
+            }
+            self.write(b"]")?;
+        }
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+        if self.scalar()? {
+            self.write(if value { b"true" } else { b"false" })?;
+        }
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        self.write_number(value)
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        self.write_number(value)
+    }
+
+    fn visit_i32(&mut self, value: i32) -> Result<(), Error> {
+        self.write_number(value)
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        if !self.scalar()? {
+            return Ok(());
+        }
+
+        if value.is_finite() {
+            write!(self.out, "{value}").map_err(Error::Write)
+        } else {
+            // JSON has no infinities
+            self.write(b"null")
+        }
+    }
+}
+
+impl<W: Write> Json<'_, '_, '_, W> {
+    // whether the map visited is the traits of the record
+    fn is_traits(&self) -> bool {
+        self.depth == 2 && self.maps == 2 && self.keys[0] == "traits"
+    }
+
+    fn write_address(&mut self, traits: &Traits) -> Result<(), Error> {
+        self.write_display("ip_address", traits.ip_address)?;
+        self.write_display("network", traits.network)
+    }
+
+    fn write_display(
+        &mut self,
+        key: &'static str,
+        value: impl std::fmt::Display,
+    ) -> Result<(), Error> {
+        self.keys[self.maps - 1] = key;
+        if matches!(self.allowed(), Allowed::No) {
+            return Ok(());
+        }
+
+        self.write_key(key)?;
+        write!(self.out, "\"{value}\"").map_err(Error::Write)?;
+        self.comma = true;
+        Ok(())
+    }
+
+    fn allowed(&self) -> Allowed {
+        let Some(fields) = self.fields else {
+            return Allowed::All;
+        };
+
+        let path = &self.keys[..self.maps.min(MAX_DEPTH)];
+        let mut allowed = Allowed::No;
+        for field in fields {
+            match strip(path, field) {
+                Strip::Under | Strip::Rest("") => return Allowed::All,
+                Strip::Rest(_) => allowed = Allowed::Partially,
+                Strip::Mismatch => {}
+            }
+        }
+
+        // the keys past the path can't be matched
+        if self.maps > MAX_DEPTH {
+            return Allowed::No;
+        }
+        allowed
+    }
+
+    /// Prepare to write a scalar, returns false if it is left out.
+    fn scalar(&mut self) -> Result<bool, Error> {
+        // only maps and arrays have fields under them
+        if self.pending.take().is_some() {
+            return Ok(false);
+        }
+
+        self.separate()?;
+        self.comma = true;
+        Ok(true)
+    }
+
+    fn start(&mut self, bracket: &[u8]) -> Result<(), Error> {
+        if let Some(key) = self.pending.take() {
+            self.write_key(key)?;
+        }
+        self.separate()?;
+        self.depth += 1;
+        self.comma = false;
+        self.write(bracket)
+    }
+
+    fn end(&mut self, bracket: &[u8]) -> Result<(), Error> {
+        self.depth -= 1;
+        self.comma = true;
+        self.write(bracket)
+    }
+
+    fn separate(&mut self) -> Result<(), Error> {
+        if std::mem::take(&mut self.comma) {
+            self.write(b",")?;
+        }
+        Ok(())
+    }
+
+    fn write_key(&mut self, key: &str) -> Result<(), Error> {
+        self.separate()?;
+        self.write_str(key)?;
+        self.write(b":")
+    }
+
+    fn write_number(&mut self, value: impl std::fmt::Display) -> Result<(), Error> {
+        if self.scalar()? {
+            write!(self.out, "{value}").map_err(Error::Write)?;
+        }
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<(), Error> {
+        write_json_str(&mut self.out, value).map_err(Error::Write)
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.out.write_all(bytes).map_err(Error::Write)
+    }
+}
+
+/// Write `value` as a JSON string, quotes included.
+pub(crate) fn write_json_str<W: Write>(out: &mut W, value: &str) -> std::io::Result<()> {
+    out.write_all(b"\"")?;
+
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let escaped: &[u8] = match b {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0..=0x1f => {
+                out.write_all(&bytes[start..i])?;
+                write!(out, "\\u{b:04x}")?;
+                start = i + 1;
+                continue;
+            }
+            _ => continue,
+        };
+
+        out.write_all(&bytes[start..i])?;
+        out.write_all(escaped)?;
+        start = i + 1;
+    }
+    out.write_all(&bytes[start..])?;
+
+    out.write_all(b"\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Json, MAX_DEPTH};
+
+    #[test]
+    fn escape() {
+        let mut json = Json {
+            out: Vec::new(),
+            fields: None,
+            traits: None,
+            wrote_traits: false,
+            keys: [""; MAX_DEPTH],
+            maps: 0,
+            depth: 0,
+            pending: None,
+            comma: false,
+        };
+        json.write_str("a\"b\\c\nd\u{1}é").unwrap();
+        assert_eq!(
+            String::from_utf8(json.out).unwrap(),
+            r#""a\"b\\c\nd\u0001é""#
+        );
+    }
+}
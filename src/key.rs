@@ -0,0 +1,184 @@
+//! Keys of the maps of the GeoIP2 databases. Decoders match on them rather
+//! than on strings, and a reader caches the key of every key pointer it
+//! reads, see [`read_key`](crate::decode::read_key).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Error;
+
+macro_rules! keys {
+    ($($key:ident => $name:literal,)*) => {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        #[repr(u8)]
+        pub(crate) enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            const ALL: &'static [Key] = &[$(Key::$key,)*];
+
+            pub(crate) fn name(self) -> &'static str {
+                match self {
+                    $(Key::$key => const {
+                        match std::str::from_utf8($name) {
+                            Ok(name) => name,
+                            Err(_) => panic!("key names are UTF-8"),
+                        }
+                    },)*
+                }
+            }
+
+            // a key is valid UTF-8 if it is one of the names
+            pub(crate) fn from_bytes(name: &[u8]) -> Option<Key> {
+                match name {
+                    $($name => Some(Key::$key),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+keys! {
+    AccuracyRadius => b"accuracy_radius",
+    AnonymizerConfidence => b"anonymizer_confidence",
+    AutonomousSystemNumber => b"autonomous_system_number",
+    AutonomousSystemOrganization => b"autonomous_system_organization",
+    AverageIncome => b"average_income",
+    City => b"city",
+    Code => b"code",
+    Confidence => b"confidence",
+    ConnectionType => b"connection_type",
+    Continent => b"continent",
+    Country => b"country",
+    CountryType => b"country_type",
+    Domain => b"domain",
+    GeonameId => b"geoname_id",
+    IpAddress => b"ip_address",
+    IpRisk => b"ip_risk",
+    Ipv4_24 => b"ipv4_24",
+    Ipv4_32 => b"ipv4_32",
+    Ipv6_32 => b"ipv6_32",
+    Ipv6_48 => b"ipv6_48",
+    Ipv6_64 => b"ipv6_64",
+    IsAnonymous => b"is_anonymous",
+    IsAnonymousProxy => b"is_anonymous_proxy",
+    IsAnonymousVpn => b"is_anonymous_vpn",
+    IsAnycast => b"is_anycast",
+    IsHostingProvider => b"is_hosting_provider",
+    IsInEuropeanUnion => b"is_in_european_union",
+    IsLegitimateProxy => b"is_legitimate_proxy",
+    IsPublicProxy => b"is_public_proxy",
+    IsResidentialProxy => b"is_residential_proxy",
+    IsSatelliteProvider => b"is_satellite_provider",
+    IsTorExitNode => b"is_tor_exit_node",
+    IsoCode => b"iso_code",
+    Isp => b"isp",
+    Latitude => b"latitude",
+    Location => b"location",
+    Longitude => b"longitude",
+    MetroCode => b"metro_code",
+    MobileCountryCode => b"mobile_country_code",
+    MobileNetworkCode => b"mobile_network_code",
+    Names => b"names",
+    Network => b"network",
+    NetworkLastSeen => b"network_last_seen",
+    Organization => b"organization",
+    PopulationDensity => b"population_density",
+    Postal => b"postal",
+    ProviderName => b"provider_name",
+    RegisteredCountry => b"registered_country",
+    RepresentedCountry => b"represented_country",
+    Score => b"score",
+    StaticIpScore => b"static_ip_score",
+    Subdivisions => b"subdivisions",
+    TimeZone => b"time_zone",
+    Traits => b"traits",
+    Type => b"type",
+    UserCount => b"user_count",
+    UserType => b"user_type",
+}
+
+impl Key {
+    /// The error of a decoder without a field for the key.
+    pub(crate) fn unknown(self) -> Error {
+        Error::UnknownField(self.name().to_string())
+    }
+}
+
+const CACHE_BITS: u32 = 10;
+
+/// The keys read through pointers, by the offset pointed to. Writers store
+/// every key once and point to it, so a database has a few hundred of them
+/// at most. The cache is direct mapped and lock free, an entry is
+/// `(offset + 1) << 8 | key` and 0 when empty.
+pub(crate) struct KeyCache {
+    slots: Box<[AtomicU64]>,
+}
+
+impl Default for KeyCache {
+    fn default() -> Self {
+        KeyCache {
+            slots: (0..1 << CACHE_BITS).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+}
+
+impl std::fmt::Debug for KeyCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyCache").finish_non_exhaustive()
+    }
+}
+
+impl KeyCache {
+    #[inline]
+    fn slot(&self, offset: usize) -> &AtomicU64 {
+        let hash = (offset as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> (64 - CACHE_BITS);
+        &self.slots[hash as usize]
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, offset: usize) -> Option<Key> {
+        let entry = self.slot(offset).load(Ordering::Relaxed);
+        if entry >> 8 == offset as u64 + 1 {
+            Key::ALL.get((entry & 0xff) as usize).copied()
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub(crate) fn insert(&self, offset: usize, key: Key) {
+        let entry = (offset as u64 + 1) << 8 | key as u64;
+        self.slot(offset).store(entry, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        for key in Key::ALL {
+            assert_eq!(Key::from_bytes(key.name().as_bytes()), Some(*key));
+            assert_eq!(Key::ALL[*key as usize], *key);
+        }
+        assert_eq!(Key::from_bytes(b"unknown"), None);
+        assert!(matches!(
+            crate::decode::read_key(crate::Data::new(b"\x47unknown"), &mut 0),
+            Err(Error::UnknownField(field)) if field == "unknown"
+        ));
+    }
+
+    #[test]
+    fn cache() {
+        let cache = KeyCache::default();
+        assert_eq!(cache.get(0), None);
+        cache.insert(0, Key::City);
+        cache.insert(12345, Key::UserType);
+        assert_eq!(cache.get(0), Some(Key::City));
+        assert_eq!(cache.get(12345), Some(Key::UserType));
+        assert_eq!(cache.get(1), None);
+    }
+}
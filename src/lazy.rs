@@ -0,0 +1,490 @@
+//! Records decoded field by field when they are asked for, for workloads
+//! that only look at a few fields of each lookup.
+
+use std::cell::OnceCell;
+use std::net::IpAddr;
+
+use crate::decode::{
+    read_bool, read_control, read_f64, read_pointer, read_str, read_u16, read_u32, read_u64,
+    skip_value, Data, Decoder, DATA_TYPE_MAP, DATA_TYPE_POINTER,
+};
+use crate::{models, Error, Reader, Value};
+
+// Top level keys are memoized up to this many, the GeoIP2 records have at
+// most 9 of them.
+const MAX_KEYS: usize = 16;
+
+/// A map of the data section whose values are decoded on access. The
+/// offsets of the keys are found on the first access and memoized.
+#[derive(Clone, Debug)]
+pub struct LazyRecord<'a> {
+    buf: Data<'a>,
+    // offset of the first key
+    offset: usize,
+    size: usize,
+    keys: OnceCell<Keys<'a>>,
+}
+
+#[derive(Clone, Debug)]
+struct Keys<'a> {
+    entries: [(&'a str, usize); MAX_KEYS],
+    len: usize,
+}
+
+impl<'a> LazyRecord<'a> {
+    fn new(buf: Data<'a>, offset: usize, size: usize) -> Self {
+        LazyRecord {
+            buf,
+            offset,
+            size,
+            keys: OnceCell::new(),
+        }
+    }
+
+    /// Number of keys of the map.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// The keys of the map, in database order.
+    pub fn keys(&self) -> Result<Vec<&'a str>, Error> {
+        let mut keys = Vec::with_capacity(self.size);
+        let mut offset = self.offset;
+        for _ in 0..self.size {
+            keys.push(read_str(self.buf, &mut offset)?);
+            skip_value(self.buf.bytes(), &mut offset)?;
+        }
+
+        Ok(keys)
+    }
+
+    pub fn contains_key(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.find(key)?.is_some())
+    }
+
+    /// Decode the value of `key` as `T`, any [`Decoder`] of the value's type,
+    /// like a model for a map, `Vec<T>` for an array or `&str` for a string.
+    pub fn get<T: Decoder<'a>>(&self, key: &str) -> Result<Option<T>, Error> {
+        match self.find(key)? {
+            Some(mut offset) => T::decode(self.buf, &mut offset).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The map under `key` as another lazy record.
+    pub fn get_map(&self, key: &str) -> Result<Option<LazyRecord<'a>>, Error> {
+        let mut offset = match self.find(key)? {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        let (mut data_type, mut size) = read_control(self.buf.bytes(), &mut offset)?;
+        if data_type == DATA_TYPE_POINTER {
+            offset = read_pointer(self.buf.bytes(), &mut offset, size)?;
+            (data_type, size) = read_control(self.buf.bytes(), &mut offset)?;
+        }
+        if data_type != DATA_TYPE_MAP {
+            return Err(Error::InvalidDataType(data_type));
+        }
+
+        Ok(Some(LazyRecord::new(self.buf, offset, size)))
+    }
+
+    pub fn get_str(&self, key: &str) -> Result<Option<&'a str>, Error> {
+        self.get_with(key, read_str)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, Error> {
+        self.get_with(key, read_bool)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Result<Option<f64>, Error> {
+        self.get_with(key, read_f64)
+    }
+
+    pub fn get_u16(&self, key: &str) -> Result<Option<u16>, Error> {
+        self.get_with(key, read_u16)
+    }
+
+    pub fn get_u32(&self, key: &str) -> Result<Option<u32>, Error> {
+        self.get_with(key, read_u32)
+    }
+
+    pub fn get_u64(&self, key: &str) -> Result<Option<u64>, Error> {
+        self.get_with(key, read_u64)
+    }
+
+    /// The value of `key` whatever its type.
+    pub fn get_value(&self, key: &str) -> Result<Option<Value<'a>>, Error> {
+        self.get_with(key, Value::decode)
+    }
+
+    #[inline]
+    fn get_with<T>(
+        &self,
+        key: &str,
+        read: impl FnOnce(Data<'a>, &mut usize) -> Result<T, Error>,
+    ) -> Result<Option<T>, Error> {
+        match self.find(key)? {
+            Some(mut offset) => read(self.buf, &mut offset).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Offset of the value of `key`.
+    fn find(&self, key: &str) -> Result<Option<usize>, Error> {
+        if self.size > MAX_KEYS {
+            return self.scan(key);
+        }
+
+        let keys = match self.keys.get() {
+            Some(keys) => keys,
+            None => {
+                let mut keys = Keys {
+                    entries: [("", 0); MAX_KEYS],
+                    len: 0,
+                };
+
+                let mut offset = self.offset;
+                for entry in keys.entries.iter_mut().take(self.size) {
+                    let key = read_str(self.buf, &mut offset)?;
+                    *entry = (key, offset);
+                    skip_value(self.buf.bytes(), &mut offset)?;
+                }
+                keys.len = self.size;
+
+                self.keys.get_or_init(|| keys)
+            }
+        };
+
+        Ok(keys.entries[..keys.len]
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, offset)| *offset))
+    }
+
+    // maps too large to memoize are scanned on every access
+    fn scan(&self, key: &str) -> Result<Option<usize>, Error> {
+        let mut offset = self.offset;
+        for _ in 0..self.size {
+            if read_str(self.buf, &mut offset)? == key {
+                return Ok(Some(offset));
+            }
+            skip_value(self.buf.bytes(), &mut offset)?;
+        }
+
+        Ok(None)
+    }
+}
+
+/// A GeoIP2 City record decoded field by field.
+#[derive(Clone, Debug)]
+pub struct LazyCity<'a> {
+    record: LazyRecord<'a>,
+}
+
+impl<'a> LazyCity<'a> {
+    /// The underlying record, to reach single nested fields without
+    /// decoding their parents, e.g. `record().get_map("traits")`.
+    pub fn record(&self) -> &LazyRecord<'a> {
+        &self.record
+    }
+
+    pub fn city(&self) -> Result<Option<models::City<'a>>, Error> {
+        self.record.get("city")
+    }
+
+    pub fn continent(&self) -> Result<Option<models::Continent<'a>>, Error> {
+        self.record.get("continent")
+    }
+
+    pub fn country(&self) -> Result<Option<models::Country<'a>>, Error> {
+        self.record.get("country")
+    }
+
+    pub fn location(&self) -> Result<Option<LazyLocation<'a>>, Error> {
+        Ok(self.record.get_map("location")?.map(LazyLocation::from))
+    }
+
+    pub fn postal(&self) -> Result<Option<models::Postal<'a>>, Error> {
+        self.record.get("postal")
+    }
+
+    pub fn registered_country(&self) -> Result<Option<models::Country<'a>>, Error> {
+        self.record.get("registered_country")
+    }
+
+    pub fn represented_country(&self) -> Result<Option<models::RepresentedCountry<'a>>, Error> {
+        self.record.get("represented_country")
+    }
+
+    pub fn subdivisions(&self) -> Result<Option<Vec<models::Subdivision<'a>>>, Error> {
+        subdivisions(&self.record)
+    }
+
+    pub fn traits(&self) -> Result<Option<LazyTraits<'a>>, Error> {
+        Ok(self.record.get_map("traits")?.map(LazyTraits::from))
+    }
+}
+
+impl<'a> From<LazyRecord<'a>> for LazyCity<'a> {
+    fn from(record: LazyRecord<'a>) -> Self {
+        LazyCity { record }
+    }
+}
+
+/// A GeoIP2 Enterprise record decoded field by field.
+#[derive(Clone, Debug)]
+pub struct LazyEnterprise<'a> {
+    record: LazyRecord<'a>,
+}
+
+impl<'a> LazyEnterprise<'a> {
+    /// The underlying record, to reach single nested fields without
+    /// decoding their parents, e.g. `record().get_map("traits")`.
+    pub fn record(&self) -> &LazyRecord<'a> {
+        &self.record
+    }
+
+    pub fn city(&self) -> Result<Option<models::EnterpriseCity<'a>>, Error> {
+        self.record.get("city")
+    }
+
+    pub fn continent(&self) -> Result<Option<models::Continent<'a>>, Error> {
+        self.record.get("continent")
+    }
+
+    pub fn country(&self) -> Result<Option<models::EnterpriseCountry<'a>>, Error> {
+        self.record.get("country")
+    }
+
+    pub fn location(&self) -> Result<Option<LazyLocation<'a>>, Error> {
+        Ok(self.record.get_map("location")?.map(LazyLocation::from))
+    }
+
+    pub fn postal(&self) -> Result<Option<models::EnterprisePostal<'a>>, Error> {
+        self.record.get("postal")
+    }
+
+    pub fn registered_country(&self) -> Result<Option<models::EnterpriseCountry<'a>>, Error> {
+        self.record.get("registered_country")
+    }
+
+    pub fn represented_country(
+        &self,
+    ) -> Result<Option<models::EnterpriseRepresentedCountry<'a>>, Error> {
+        self.record.get("represented_country")
+    }
+
+    pub fn subdivisions(&self) -> Result<Option<Vec<models::EnterpriseSubdivision<'a>>>, Error> {
+        subdivisions(&self.record)
+    }
+
+    pub fn traits(&self) -> Result<Option<LazyTraits<'a>>, Error> {
+        Ok(self.record.get_map("traits")?.map(LazyTraits::from))
+    }
+}
+
+impl<'a> From<LazyRecord<'a>> for LazyEnterprise<'a> {
+    fn from(record: LazyRecord<'a>) -> Self {
+        LazyEnterprise { record }
+    }
+}
+
+// an accessor for every field of a nested record, named after its key
+macro_rules! fields {
+    ($($name:ident: $ty:ty => $get:ident,)*) => {
+        $(
+            pub fn $name(&self) -> Result<Option<$ty>, Error> {
+                self.record.$get(stringify!($name))
+            }
+        )*
+    };
+}
+
+/// The `location` of a [`LazyCity`] or [`LazyEnterprise`], decoded field
+/// by field.
+#[derive(Clone, Debug)]
+pub struct LazyLocation<'a> {
+    record: LazyRecord<'a>,
+}
+
+impl<'a> LazyLocation<'a> {
+    pub fn record(&self) -> &LazyRecord<'a> {
+        &self.record
+    }
+
+    fields! {
+        latitude: f64 => get_f64,
+        longitude: f64 => get_f64,
+        accuracy_radius: u16 => get_u16,
+        time_zone: &'a str => get_str,
+        metro_code: u16 => get_u16,
+        average_income: u32 => get_u32,
+        population_density: u32 => get_u32,
+    }
+}
+
+impl<'a> From<LazyRecord<'a>> for LazyLocation<'a> {
+    fn from(record: LazyRecord<'a>) -> Self {
+        LazyLocation { record }
+    }
+}
+
+/// The `traits` of a [`LazyCity`] or [`LazyEnterprise`], decoded field by
+/// field.
+#[derive(Clone, Debug)]
+pub struct LazyTraits<'a> {
+    record: LazyRecord<'a>,
+}
+
+impl<'a> LazyTraits<'a> {
+    pub fn record(&self) -> &LazyRecord<'a> {
+        &self.record
+    }
+
+    fields! {
+        autonomous_system_number: u32 => get_u32,
+        autonomous_system_organization: &'a str => get_str,
+        connection_type: &'a str => get_str,
+        domain: &'a str => get_str,
+        ip_address: &'a str => get_str,
+        is_anonymous: bool => get_bool,
+        is_anonymous_proxy: bool => get_bool,
+        is_anonymous_vpn: bool => get_bool,
+        is_anycast: bool => get_bool,
+        is_hosting_provider: bool => get_bool,
+        is_legitimate_proxy: bool => get_bool,
+        is_public_proxy: bool => get_bool,
+        is_residential_proxy: bool => get_bool,
+        is_satellite_provider: bool => get_bool,
+        is_tor_exit_node: bool => get_bool,
+        isp: &'a str => get_str,
+        mobile_country_code: &'a str => get_str,
+        mobile_network_code: &'a str => get_str,
+        network: &'a str => get_str,
+        organization: &'a str => get_str,
+        static_ip_score: f64 => get_f64,
+        user_count: u32 => get_u32,
+        user_type: &'a str => get_str,
+    }
+
+    pub fn connection_kind(&self) -> Result<Option<models::ConnectionKind<'a>>, Error> {
+        Ok(self.connection_type()?.map(models::ConnectionKind::from))
+    }
+
+    pub fn user_kind(&self) -> Result<Option<models::UserKind<'a>>, Error> {
+        Ok(self.user_type()?.map(models::UserKind::from))
+    }
+}
+
+impl<'a> From<LazyRecord<'a>> for LazyTraits<'a> {
+    fn from(record: LazyRecord<'a>) -> Self {
+        LazyTraits { record }
+    }
+}
+
+fn subdivisions<'a, T: Decoder<'a>>(record: &LazyRecord<'a>) -> Result<Option<Vec<T>>, Error> {
+    match record.find("subdivisions")? {
+        Some(mut offset) => Vec::decode(record.buf, &mut offset).map(Some),
+        None => Ok(None),
+    }
+}
+
+impl<'a, S: AsRef<[u8]>> Reader<S> {
+    /// Lookup the address without decoding its record, fields are decoded
+    /// when they are accessed. See [`LazyCity`] and [`LazyEnterprise`] for
+    /// typed accessors.
+    pub fn lookup_lazy(&'a self, addr: IpAddr) -> Result<LazyRecord<'a>, Error> {
+        let (offset, size) = self.find_record(addr)?;
+        Ok(LazyRecord::new(self.data(), offset, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lazy_city() {
+        let reader = Reader::open_file("testdata/GeoIP2-City-Test.mmdb").unwrap();
+        let city = LazyCity::from(reader.lookup_lazy("81.2.69.142".parse().unwrap()).unwrap());
+
+        let country = city.country().unwrap().unwrap();
+        assert_eq!(country.iso_code, Some("GB"));
+        let location = city.location().unwrap().unwrap();
+        assert_eq!(location.latitude().unwrap(), Some(51.5142));
+        assert_eq!(location.time_zone().unwrap(), Some("Europe/London"));
+        assert_eq!(location.metro_code().unwrap(), None);
+        assert!(city.traits().unwrap().is_none());
+        assert_eq!(city.subdivisions().unwrap().unwrap().len(), 1);
+        assert!(city.postal().unwrap().is_none());
+        assert!(city.represented_country().unwrap().is_none());
+
+        let record = city.record();
+        assert!(record.contains_key("city").unwrap());
+        assert!(!record.contains_key("postal").unwrap());
+        assert_eq!(record.keys().unwrap().len(), record.len());
+        let names = record
+            .get_map("city")
+            .unwrap()
+            .unwrap()
+            .get::<models::Names>("names")
+            .unwrap()
+            .unwrap();
+        assert_eq!(names.get("en"), Some("London"));
+        assert_eq!(
+            record
+                .get_map("location")
+                .unwrap()
+                .unwrap()
+                .get_u16("accuracy_radius")
+                .unwrap(),
+            Some(10)
+        );
+        assert_eq!(
+            record
+                .get_map("country")
+                .unwrap()
+                .unwrap()
+                .get_str("iso_code")
+                .unwrap(),
+            Some("GB")
+        );
+        assert!(record.get_str("city").is_err());
+    }
+
+    #[test]
+    fn lazy_enterprise() {
+        let reader = Reader::open_file("testdata/GeoIP2-Enterprise-Test.mmdb").unwrap();
+        let enterprise =
+            LazyEnterprise::from(reader.lookup_lazy("74.209.24.0".parse().unwrap()).unwrap());
+
+        let traits = enterprise.record().get_map("traits").unwrap().unwrap();
+        assert_eq!(
+            traits.get_str("isp").unwrap(),
+            Some("Fairpoint Communications")
+        );
+        assert_eq!(traits.get_f64("static_ip_score").unwrap(), Some(0.34));
+        assert_eq!(
+            traits.get_u32("autonomous_system_number").unwrap(),
+            Some(14671)
+        );
+        assert_eq!(traits.get_bool("is_tor_exit_node").unwrap(), None);
+
+        let city = enterprise.city().unwrap().unwrap();
+        assert_eq!(city.confidence, Some(11));
+        let traits = enterprise.traits().unwrap().unwrap();
+        assert_eq!(traits.isp().unwrap(), Some("Fairpoint Communications"));
+        assert_eq!(traits.user_type().unwrap(), Some("residential"));
+        assert_eq!(
+            traits.user_kind().unwrap(),
+            Some(models::UserKind::Residential)
+        );
+        assert_eq!(traits.is_anycast().unwrap(), None);
+    }
+}
@@ -1,15 +1,40 @@
 #![deny(trivial_casts, trivial_numeric_casts, unused_import_braces)]
 
+mod builder;
+mod codegen;
 mod decode;
+pub mod disasm;
+mod dot;
 mod errors;
+pub mod explain;
+mod flatten;
+mod json;
+mod key;
+mod lazy;
 mod metadata;
 pub mod models;
+mod network;
+pub mod owned;
 mod reader;
+pub mod schema;
+mod semconv;
+mod value;
+mod visit;
 
+pub use builder::ReaderBuilder;
+pub use decode::{Data, Decoder};
 pub use errors::Error;
+pub use flatten::{Flatten, FlattenOptions, FlattenRecord, IndexFormat};
+pub use json::JsonOptions;
+pub use lazy::{LazyCity, LazyEnterprise, LazyLocation, LazyRecord, LazyTraits};
+pub use network::Network;
 pub use reader::{
-    AnonymousIp, Asn, City, ConnectionType, Country, Domain, Enterprise, Isp, Reader,
+    AnonymousIp, AnonymousPlus, Asn, City, ConnectionType, Country, DensityIncome, Domain,
+    Enterprise, IpRisk, Isp, Reader, StaticIpScore, UserCount,
 };
+pub use semconv::{AttributeValue, Attributes};
+pub use value::Value;
+pub use visit::{Visitor, Walk};
 
 #[cfg(feature = "mmap")]
 pub use memmap2::Mmap;
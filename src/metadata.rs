@@ -1,4 +1,6 @@
-use crate::decode::{read_control, read_map, read_str, read_str_array, read_usize};
+use crate::decode::{
+    read_control, read_map, read_str, read_u16, read_u64, read_usize, Data, Decoder,
+};
 use crate::Error;
 
 #[derive(Debug, Default)]
@@ -18,25 +20,26 @@ impl<'a> Metadata<'a> {
     pub(crate) fn from_bytes(buf: &'a [u8]) -> Result<Self, Error> {
         let mut offset = 0;
         let (_data_type, size) = read_control(buf, &mut offset)?;
+        let buf = Data::new(buf);
         let mut metadata = Metadata::default();
 
         for _ in 0..size {
             match read_str(buf, &mut offset)? {
                 "binary_format_major_version" => {
-                    metadata.binary_format_major_version = read_usize(buf, &mut offset)? as u16;
+                    metadata.binary_format_major_version = read_u16(buf, &mut offset)?;
                 }
                 "binary_format_minor_version" => {
-                    metadata.binary_format_minor_version = read_usize(buf, &mut offset)? as u16;
+                    metadata.binary_format_minor_version = read_u16(buf, &mut offset)?;
                 }
                 "node_count" => metadata.node_count = read_usize(buf, &mut offset)?,
                 "record_size" => {
                     metadata.record_size = read_usize(buf, &mut offset)?;
                 }
-                "ip_version" => metadata.ip_version = read_usize(buf, &mut offset)? as u16,
+                "ip_version" => metadata.ip_version = read_u16(buf, &mut offset)?,
                 "database_type" => metadata.database_type = read_str(buf, &mut offset)?,
-                "languages" => metadata.languages = read_str_array(buf, &mut offset)?,
+                "languages" => metadata.languages = Vec::decode(buf, &mut offset)?,
                 "build_epoch" => {
-                    metadata.build_epoch = read_usize(buf, &mut offset)? as u64;
+                    metadata.build_epoch = read_u64(buf, &mut offset)?;
                 }
                 "description" => metadata.description = read_map(buf, &mut offset)?,
                 field => return Err(Error::UnknownField(field.into())),
@@ -52,9 +55,9 @@ impl<'a> Metadata<'a> {
     }
 }
 
-pub(crate) fn find_metadata_start(buf: &[u8]) -> Result<usize, Error> {
-    const METADATA_START_MARKER: &[u8] = b"\xab\xcd\xefMaxMind.com";
+pub(crate) const METADATA_START_MARKER: &[u8] = b"\xab\xcd\xefMaxMind.com";
 
+pub(crate) fn find_metadata_start(buf: &[u8]) -> Result<usize, Error> {
     let window = METADATA_START_MARKER.len();
     let mut pos = buf.len() - window;
 
@@ -1,21 +1,331 @@
-use crate::decode::{read_bool, read_f64, read_map, read_str, read_usize, Decoder};
+use std::fmt::{Display, Formatter};
+
+use crate::decode::{
+    read_bool, read_f64, read_key, read_str, read_str_unchecked, read_u16, read_u32, Data, Decoder,
+    DATA_TYPE_MAP,
+};
+use crate::key::Key;
 use crate::Error;
 
+/// Localized names of a place, keyed by locale code like `en` or `zh-CN`.
+///
+/// The names are read from the data section when they are asked for, so
+/// decoding a record doesn't allocate for them.
+#[derive(Clone, Copy, Default)]
+pub struct Names<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> Names<'a> {
+    /// The name in exactly `language`.
+    pub fn get(&self, language: &str) -> Option<&'a str> {
+        self.iter()
+            .find(|(locale, _)| *locale == language)
+            .map(|(_, name)| name)
+    }
+
+    /// The name in the first of `languages` available, e.g.
+    /// `names.best(&["zh-CN", "en"])`.
+    pub fn best<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        languages
+            .iter()
+            .find_map(|language| self.get(language.as_ref()))
+    }
+
+    /// Iterate `(locale, name)` pairs in database order.
+    pub fn iter(&self) -> NamesIter<'a> {
+        NamesIter {
+            buf: self.buf,
+            offset: self.offset,
+            remaining: self.len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'a> Decoder<'a> for Names<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let start = *offset;
+        // validate the names once, so iterating can't fail later
+        for _ in 0..size {
+            read_str(buf, offset)?;
+            read_str(buf, offset)?;
+        }
+
+        Ok(Names {
+            buf: buf.bytes(),
+            offset: start,
+            len: size,
+        })
+    }
+}
+
+impl PartialEq for Names<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl std::fmt::Debug for Names<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &Names<'a> {
+    type Item = (&'a str, &'a str);
+    type IntoIter = NamesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the `(locale, name)` pairs of [`Names`].
+#[derive(Clone, Debug)]
+pub struct NamesIter<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for NamesIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        // SAFETY: the names were all read with `read_str` when decoded
+        let locale = unsafe { read_str_unchecked(self.buf, &mut self.offset) }.ok()?;
+        let name = unsafe { read_str_unchecked(self.buf, &mut self.offset) }.ok()?;
+        Some((locale, name))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for NamesIter<'_> {}
+
+/// Kind of network connection, `connection_type` of the Connection-Type and
+/// Enterprise databases.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConnectionKind<'a> {
+    Dialup,
+    CableDsl,
+    Corporate,
+    Cellular,
+    Satellite,
+    /// A value this version doesn't know about.
+    Other(&'a str),
+}
+
+impl<'a> ConnectionKind<'a> {
+    /// The value as stored in the database.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            ConnectionKind::Dialup => "Dialup",
+            ConnectionKind::CableDsl => "Cable/DSL",
+            ConnectionKind::Corporate => "Corporate",
+            ConnectionKind::Cellular => "Cellular",
+            ConnectionKind::Satellite => "Satellite",
+            ConnectionKind::Other(s) => s,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for ConnectionKind<'a> {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "Dialup" => ConnectionKind::Dialup,
+            "Cable/DSL" => ConnectionKind::CableDsl,
+            "Corporate" => ConnectionKind::Corporate,
+            "Cellular" => ConnectionKind::Cellular,
+            "Satellite" => ConnectionKind::Satellite,
+            _ => ConnectionKind::Other(s),
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for ConnectionKind<'a> {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        read_str(buf, offset).map(ConnectionKind::from)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+impl Display for ConnectionKind<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Kind of user of the network, `user_type` of the Enterprise and Insights
+/// databases.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum UserKind<'a> {
+    Business,
+    Cafe,
+    Cellular,
+    College,
+    ConsumerPrivacyNetwork,
+    ContentDeliveryNetwork,
+    Dialup,
+    Government,
+    Hosting,
+    Library,
+    Military,
+    Residential,
+    Router,
+    School,
+    SearchEngineSpider,
+    Traveler,
+    /// A value this version doesn't know about.
+    Other(&'a str),
+}
+
+impl<'a> UserKind<'a> {
+    /// The value as stored in the database.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            UserKind::Business => "business",
+            UserKind::Cafe => "cafe",
+            UserKind::Cellular => "cellular",
+            UserKind::College => "college",
+            UserKind::ConsumerPrivacyNetwork => "consumer_privacy_network",
+            UserKind::ContentDeliveryNetwork => "content_delivery_network",
+            UserKind::Dialup => "dialup",
+            UserKind::Government => "government",
+            UserKind::Hosting => "hosting",
+            UserKind::Library => "library",
+            UserKind::Military => "military",
+            UserKind::Residential => "residential",
+            UserKind::Router => "router",
+            UserKind::School => "school",
+            UserKind::SearchEngineSpider => "search_engine_spider",
+            UserKind::Traveler => "traveler",
+            UserKind::Other(s) => s,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for UserKind<'a> {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "business" => UserKind::Business,
+            "cafe" => UserKind::Cafe,
+            "cellular" => UserKind::Cellular,
+            "college" => UserKind::College,
+            "consumer_privacy_network" => UserKind::ConsumerPrivacyNetwork,
+            "content_delivery_network" => UserKind::ContentDeliveryNetwork,
+            "dialup" => UserKind::Dialup,
+            "government" => UserKind::Government,
+            "hosting" => UserKind::Hosting,
+            "library" => UserKind::Library,
+            "military" => UserKind::Military,
+            "residential" => UserKind::Residential,
+            "router" => UserKind::Router,
+            "school" => UserKind::School,
+            "search_engine_spider" => UserKind::SearchEngineSpider,
+            "traveler" => UserKind::Traveler,
+            _ => UserKind::Other(s),
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for UserKind<'a> {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        read_str(buf, offset).map(UserKind::from)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+impl Display for UserKind<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a represented country differs from the country, e.g. a military base
+/// abroad.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RepresentationKind<'a> {
+    Military,
+    /// A value this version doesn't know about.
+    Other(&'a str),
+}
+
+impl<'a> RepresentationKind<'a> {
+    /// The value as stored in the database.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            RepresentationKind::Military => "military",
+            RepresentationKind::Other(s) => s,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for RepresentationKind<'a> {
+    fn from(s: &'a str) -> Self {
+        match s {
+            "military" => RepresentationKind::Military,
+            _ => RepresentationKind::Other(s),
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for RepresentationKind<'a> {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        read_str(buf, offset).map(RepresentationKind::from)
+    }
+
+    fn decode_with_size(_buf: Data<'a>, _offset: &mut usize, _size: usize) -> Result<Self, Error> {
+        Err(Error::InvalidDataType(DATA_TYPE_MAP))
+    }
+}
+
+impl Display for RepresentationKind<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct City<'a> {
+    pub confidence: Option<u16>,
     pub geoname_id: Option<u32>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
 }
 
 impl<'a> Decoder<'a> for City<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut city = City::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "geoname_id" => city.geoname_id = Some(read_usize(buf, offset)? as u32),
-                "names" => city.names = Some(read_map(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Confidence => city.confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => city.geoname_id = Some(read_u32(buf, offset)?),
+                Key::Names => city.names = Some(Names::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -27,19 +337,19 @@ impl<'a> Decoder<'a> for City<'a> {
 pub struct Continent<'a> {
     pub geoname_id: Option<u32>,
     pub code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
 }
 
 impl<'a> Decoder<'a> for Continent<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut continent = Continent::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "geoname_id" => continent.geoname_id = Some(read_usize(buf, offset)? as u32),
-                "code" => continent.code = Some(read_str(buf, offset)?),
-                "names" => continent.names = Some(read_map(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::GeonameId => continent.geoname_id = Some(read_u32(buf, offset)?),
+                Key::Code => continent.code = Some(read_str(buf, offset)?),
+                Key::Names => continent.names = Some(Names::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -49,25 +359,27 @@ impl<'a> Decoder<'a> for Continent<'a> {
 
 #[derive(Debug, Default)]
 pub struct Country<'a> {
+    pub confidence: Option<u16>,
     pub geoname_id: Option<u32>,
     pub is_in_european_union: Option<bool>,
     pub iso_code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
 }
 
 impl<'a> Decoder<'a> for Country<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut country = Country::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "geoname_id" => country.geoname_id = Some(read_usize(buf, offset)? as u32),
-                "is_in_european_union" => {
+            match read_key(buf, offset)? {
+                Key::Confidence => country.confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => country.geoname_id = Some(read_u32(buf, offset)?),
+                Key::IsInEuropeanUnion => {
                     country.is_in_european_union = Some(read_bool(buf, offset)?)
                 }
-                "iso_code" => country.iso_code = Some(read_str(buf, offset)?),
-                "names" => country.names = Some(read_map(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::IsoCode => country.iso_code = Some(read_str(buf, offset)?),
+                Key::Names => country.names = Some(Names::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -80,27 +392,31 @@ pub struct RepresentedCountry<'a> {
     pub geoname_id: Option<u32>,
     pub is_in_european_union: Option<bool>,
     pub iso_code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
     // type actually
     pub representation_type: Option<&'a str>,
 }
 
+impl<'a> RepresentedCountry<'a> {
+    pub fn representation_kind(&self) -> Option<RepresentationKind<'a>> {
+        self.representation_type.map(RepresentationKind::from)
+    }
+}
+
 impl<'a> Decoder<'a> for RepresentedCountry<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut represented_country = RepresentedCountry::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "geoname_id" => {
-                    represented_country.geoname_id = Some(read_usize(buf, offset)? as u32)
-                }
-                "is_in_european_union" => {
+            match read_key(buf, offset)? {
+                Key::GeonameId => represented_country.geoname_id = Some(read_u32(buf, offset)?),
+                Key::IsInEuropeanUnion => {
                     represented_country.is_in_european_union = Some(read_bool(buf, offset)?)
                 }
-                "iso_code" => represented_country.iso_code = Some(read_str(buf, offset)?),
-                "names" => represented_country.names = Some(read_map(buf, offset)?),
-                "type" => represented_country.representation_type = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::IsoCode => represented_country.iso_code = Some(read_str(buf, offset)?),
+                Key::Names => represented_country.names = Some(Names::decode(buf, offset)?),
+                Key::Type => represented_country.representation_type = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -108,25 +424,91 @@ impl<'a> Decoder<'a> for RepresentedCountry<'a> {
     }
 }
 
-#[derive(Debug, Default)]
-pub struct Traits {
+#[derive(Clone, Debug, Default)]
+pub struct Traits<'a> {
+    pub autonomous_system_number: Option<u32>,
+    pub autonomous_system_organization: Option<&'a str>,
+    pub connection_type: Option<&'a str>,
+    pub domain: Option<&'a str>,
+    /// The address that was looked up, only in Insights shaped records.
+    pub ip_address: Option<&'a str>,
+    pub is_anonymous: Option<bool>,
+    /// Deprecated by MaxMind, see `is_anonymous` and the Anonymous IP
+    /// database.
     pub is_anonymous_proxy: Option<bool>,
+    pub is_anonymous_vpn: Option<bool>,
     pub is_anycast: Option<bool>,
+    pub is_hosting_provider: Option<bool>,
+    pub is_legitimate_proxy: Option<bool>,
+    pub is_public_proxy: Option<bool>,
+    pub is_residential_proxy: Option<bool>,
+    /// Deprecated by MaxMind.
     pub is_satellite_provider: Option<bool>,
+    pub is_tor_exit_node: Option<bool>,
+    pub isp: Option<&'a str>,
+    pub mobile_country_code: Option<&'a str>,
+    pub mobile_network_code: Option<&'a str>,
+    /// The network of the record, like `81.2.69.0/24`, only in Insights
+    /// shaped records.
+    pub network: Option<&'a str>,
+    pub organization: Option<&'a str>,
+    pub static_ip_score: Option<f64>,
+    pub user_count: Option<u32>,
+    pub user_type: Option<&'a str>,
+}
+
+impl<'a> Traits<'a> {
+    pub fn connection_kind(&self) -> Option<ConnectionKind<'a>> {
+        self.connection_type.map(ConnectionKind::from)
+    }
+
+    pub fn user_kind(&self) -> Option<UserKind<'a>> {
+        self.user_type.map(UserKind::from)
+    }
 }
 
-impl<'a> Decoder<'a> for Traits {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+impl<'a> Decoder<'a> for Traits<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut traits = Traits::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "is_anonymous_proxy" => traits.is_anonymous_proxy = Some(read_bool(buf, offset)?),
-                "is_anycast" => traits.is_anycast = Some(read_bool(buf, offset)?),
-                "is_satellite_provider" => {
+            match read_key(buf, offset)? {
+                Key::AutonomousSystemNumber => {
+                    traits.autonomous_system_number = Some(read_u32(buf, offset)?)
+                }
+                Key::AutonomousSystemOrganization => {
+                    traits.autonomous_system_organization = Some(read_str(buf, offset)?)
+                }
+                Key::ConnectionType => traits.connection_type = Some(read_str(buf, offset)?),
+                Key::Domain => traits.domain = Some(read_str(buf, offset)?),
+                Key::IpAddress => traits.ip_address = Some(read_str(buf, offset)?),
+                Key::IsAnonymous => traits.is_anonymous = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousProxy => traits.is_anonymous_proxy = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousVpn => traits.is_anonymous_vpn = Some(read_bool(buf, offset)?),
+                Key::IsAnycast => traits.is_anycast = Some(read_bool(buf, offset)?),
+                Key::IsHostingProvider => {
+                    traits.is_hosting_provider = Some(read_bool(buf, offset)?)
+                }
+                Key::IsLegitimateProxy => {
+                    traits.is_legitimate_proxy = Some(read_bool(buf, offset)?)
+                }
+                Key::IsPublicProxy => traits.is_public_proxy = Some(read_bool(buf, offset)?),
+                Key::IsResidentialProxy => {
+                    traits.is_residential_proxy = Some(read_bool(buf, offset)?)
+                }
+                Key::IsSatelliteProvider => {
                     traits.is_satellite_provider = Some(read_bool(buf, offset)?)
                 }
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::IsTorExitNode => traits.is_tor_exit_node = Some(read_bool(buf, offset)?),
+                Key::Isp => traits.isp = Some(read_str(buf, offset)?),
+                Key::MobileCountryCode => traits.mobile_country_code = Some(read_str(buf, offset)?),
+                Key::MobileNetworkCode => traits.mobile_network_code = Some(read_str(buf, offset)?),
+                Key::Network => traits.network = Some(read_str(buf, offset)?),
+                Key::Organization => traits.organization = Some(read_str(buf, offset)?),
+                Key::StaticIpScore => traits.static_ip_score = Some(read_f64(buf, offset)?),
+                Key::UserCount => traits.user_count = Some(read_u32(buf, offset)?),
+                Key::UserType => traits.user_type = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -141,22 +523,28 @@ pub struct Location<'a> {
     pub accuracy_radius: Option<u16>,
     pub time_zone: Option<&'a str>,
     pub metro_code: Option<u16>,
+    /// Average income in US dollars of the postal code.
+    pub average_income: Option<u32>,
+    /// Population per square kilometer of the postal code.
+    pub population_density: Option<u32>,
 }
 
 impl<'a> Decoder<'a> for Location<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut location = Location::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "latitude" => location.latitude = Some(read_f64(buf, offset)?),
-                "longitude" => location.longitude = Some(read_f64(buf, offset)?),
-                "accuracy_radius" => {
-                    location.accuracy_radius = Some(read_usize(buf, offset)? as u16)
+            match read_key(buf, offset)? {
+                Key::Latitude => location.latitude = Some(read_f64(buf, offset)?),
+                Key::Longitude => location.longitude = Some(read_f64(buf, offset)?),
+                Key::AccuracyRadius => location.accuracy_radius = Some(read_u16(buf, offset)?),
+                Key::TimeZone => location.time_zone = Some(read_str(buf, offset)?),
+                Key::MetroCode => location.metro_code = Some(read_u16(buf, offset)?),
+                Key::AverageIncome => location.average_income = Some(read_u32(buf, offset)?),
+                Key::PopulationDensity => {
+                    location.population_density = Some(read_u32(buf, offset)?)
                 }
-                "time_zone" => location.time_zone = Some(read_str(buf, offset)?),
-                "metro_code" => location.metro_code = Some(read_usize(buf, offset)? as u16),
-                field => return Err(Error::UnknownField(field.to_string())),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -167,16 +555,18 @@ impl<'a> Decoder<'a> for Location<'a> {
 #[derive(Debug, Default)]
 pub struct Postal<'a> {
     pub code: Option<&'a str>,
+    pub confidence: Option<u16>,
 }
 
 impl<'a> Decoder<'a> for Postal<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut postal = Postal::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "code" => postal.code = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Code => postal.code = Some(read_str(buf, offset)?),
+                Key::Confidence => postal.confidence = Some(read_u16(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -186,21 +576,23 @@ impl<'a> Decoder<'a> for Postal<'a> {
 
 #[derive(Debug, Default)]
 pub struct Subdivision<'a> {
+    pub confidence: Option<u16>,
     pub geoname_id: Option<u32>,
     pub iso_code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
 }
 
 impl<'a> Decoder<'a> for Subdivision<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut subdivision = Subdivision::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "geoname_id" => subdivision.geoname_id = Some(read_usize(buf, offset)? as u32),
-                "iso_code" => subdivision.iso_code = Some(read_str(buf, offset)?),
-                "names" => subdivision.names = Some(read_map(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Confidence => subdivision.confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => subdivision.geoname_id = Some(read_u32(buf, offset)?),
+                Key::IsoCode => subdivision.iso_code = Some(read_str(buf, offset)?),
+                Key::Names => subdivision.names = Some(Names::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -212,29 +604,25 @@ impl<'a> Decoder<'a> for Subdivision<'a> {
 pub struct EnterpriseCountry<'a> {
     pub geoname_id: Option<u32>,
     pub iso_code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
     pub is_in_european_union: Option<bool>,
     pub confidence: Option<u16>,
 }
 
 impl<'a> Decoder<'a> for EnterpriseCountry<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut enterprise_country = EnterpriseCountry::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "confidence" => {
-                    enterprise_country.confidence = Some(read_usize(buf, offset)? as u16)
-                }
-                "geoname_id" => {
-                    enterprise_country.geoname_id = Some(read_usize(buf, offset)? as u32)
-                }
-                "iso_code" => enterprise_country.iso_code = Some(read_str(buf, offset)?),
-                "names" => enterprise_country.names = Some(read_map(buf, offset)?),
-                "is_in_european_union" => {
+            match read_key(buf, offset)? {
+                Key::Confidence => enterprise_country.confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => enterprise_country.geoname_id = Some(read_u32(buf, offset)?),
+                Key::IsoCode => enterprise_country.iso_code = Some(read_str(buf, offset)?),
+                Key::Names => enterprise_country.names = Some(Names::decode(buf, offset)?),
+                Key::IsInEuropeanUnion => {
                     enterprise_country.is_in_european_union = Some(read_bool(buf, offset)?)
                 }
-                field => return Err(Error::UnknownField(field.to_string())),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -247,26 +635,33 @@ pub struct EnterpriseRepresentedCountry<'a> {
     pub confidence: Option<u16>,
     pub geoname_id: Option<u32>,
     pub iso_code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
     pub is_in_european_union: Option<bool>,
     pub country_type: Option<&'a str>,
 }
 
+impl<'a> EnterpriseRepresentedCountry<'a> {
+    pub fn representation_kind(&self) -> Option<RepresentationKind<'a>> {
+        self.country_type.map(RepresentationKind::from)
+    }
+}
+
 impl<'a> Decoder<'a> for EnterpriseRepresentedCountry<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut country = EnterpriseRepresentedCountry::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "confidence" => country.confidence = Some(read_usize(buf, offset)? as u16),
-                "geoname_id" => country.geoname_id = Some(read_usize(buf, offset)? as u32),
-                "iso_code" => country.iso_code = Some(read_str(buf, offset)?),
-                "names" => country.names = Some(read_map(buf, offset)?),
-                "is_in_european_union" => {
+            match read_key(buf, offset)? {
+                Key::Confidence => country.confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => country.geoname_id = Some(read_u32(buf, offset)?),
+                Key::IsoCode => country.iso_code = Some(read_str(buf, offset)?),
+                Key::Names => country.names = Some(Names::decode(buf, offset)?),
+                Key::IsInEuropeanUnion => {
                     country.is_in_european_union = Some(read_bool(buf, offset)?)
                 }
-                "country_type" => country.country_type = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                // the databases use `type`, like `RepresentedCountry`
+                Key::Type | Key::CountryType => country.country_type = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -278,19 +673,19 @@ impl<'a> Decoder<'a> for EnterpriseRepresentedCountry<'a> {
 pub struct EnterpriseCity<'a> {
     pub confidence: Option<u16>,
     pub geoname_id: Option<u32>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
 }
 
 impl<'a> Decoder<'a> for EnterpriseCity<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut city = EnterpriseCity::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "confidence" => city.confidence = Some(read_usize(buf, offset)? as u16),
-                "geoname_id" => city.geoname_id = Some(read_usize(buf, offset)? as u32),
-                "names" => city.names = Some(read_map(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Confidence => city.confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => city.geoname_id = Some(read_u32(buf, offset)?),
+                Key::Names => city.names = Some(Names::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -305,14 +700,14 @@ pub struct EnterprisePostal<'a> {
 }
 
 impl<'a> Decoder<'a> for EnterprisePostal<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut postal = EnterprisePostal::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "confidence" => postal.confidence = Some(read_usize(buf, offset)? as u16),
-                "code" => postal.code = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Confidence => postal.confidence = Some(read_u16(buf, offset)?),
+                Key::Code => postal.code = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -325,23 +720,23 @@ pub struct EnterpriseSubdivision<'a> {
     pub confidence: Option<u16>,
     pub geoname_id: Option<u32>,
     pub iso_code: Option<&'a str>,
-    pub names: Option<Vec<(&'a str, &'a str)>>,
+    pub names: Option<Names<'a>>,
 }
 
 impl<'a> Decoder<'a> for EnterpriseSubdivision<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut confidence = None;
         let mut geoname_id = None;
         let mut iso_code = None;
         let mut names = None;
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "confidence" => confidence = Some(read_usize(buf, offset)? as u16),
-                "geoname_id" => geoname_id = Some(read_usize(buf, offset)? as u32),
-                "iso_code" => iso_code = Some(read_str(buf, offset)?),
-                "names" => names = Some(read_map(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Confidence => confidence = Some(read_u16(buf, offset)?),
+                Key::GeonameId => geoname_id = Some(read_u32(buf, offset)?),
+                Key::IsoCode => iso_code = Some(read_str(buf, offset)?),
+                Key::Names => names = Some(Names::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -360,6 +755,7 @@ pub struct EnterpriseTraits<'a> {
     pub autonomous_system_organization: Option<&'a str>,
     pub connection_type: Option<&'a str>,
     pub domain: Option<&'a str>,
+    pub ip_address: Option<&'a str>,
     pub is_anonymous: Option<bool>,
     pub is_anonymous_proxy: Option<bool>,
     pub is_anonymous_vpn: Option<bool>,
@@ -374,16 +770,29 @@ pub struct EnterpriseTraits<'a> {
     pub is_tor_exit_node: Option<bool>,
     pub mobile_country_code: Option<&'a str>,
     pub mobile_network_code: Option<&'a str>,
+    pub network: Option<&'a str>,
     pub organization: Option<&'a str>,
+    pub user_count: Option<u32>,
     pub user_type: Option<&'a str>,
 }
 
+impl<'a> EnterpriseTraits<'a> {
+    pub fn connection_kind(&self) -> Option<ConnectionKind<'a>> {
+        self.connection_type.map(ConnectionKind::from)
+    }
+
+    pub fn user_kind(&self) -> Option<UserKind<'a>> {
+        self.user_type.map(UserKind::from)
+    }
+}
+
 impl<'a> Decoder<'a> for EnterpriseTraits<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut autonomous_system_number = None;
         let mut autonomous_system_organization = None;
         let mut connection_type = None;
         let mut domain = None;
+        let mut ip_address = None;
         let mut is_anonymous = None;
         let mut is_anonymous_proxy = None;
         let mut is_anonymous_vpn = None;
@@ -398,36 +807,41 @@ impl<'a> Decoder<'a> for EnterpriseTraits<'a> {
         let mut is_tor_exit_node = None;
         let mut mobile_country_code = None;
         let mut mobile_network_code = None;
+        let mut network = None;
         let mut organization = None;
+        let mut user_count = None;
         let mut user_type = None;
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "autonomous_system_number" => {
-                    autonomous_system_number = Some(read_usize(buf, offset)? as u32)
+            match read_key(buf, offset)? {
+                Key::AutonomousSystemNumber => {
+                    autonomous_system_number = Some(read_u32(buf, offset)?)
                 }
-                "autonomous_system_organization" => {
+                Key::AutonomousSystemOrganization => {
                     autonomous_system_organization = Some(read_str(buf, offset)?)
                 }
-                "connection_type" => connection_type = Some(read_str(buf, offset)?),
-                "domain" => domain = Some(read_str(buf, offset)?),
-                "is_anonymous" => is_anonymous = Some(read_bool(buf, offset)?),
-                "is_anonymous_proxy" => is_anonymous_proxy = Some(read_bool(buf, offset)?),
-                "is_anonymous_vpn" => is_anonymous_vpn = Some(read_bool(buf, offset)?),
-                "is_anycast" => is_anycast = Some(read_bool(buf, offset)?),
-                "is_hosting_provider" => is_hosting_provider = Some(read_bool(buf, offset)?),
-                "isp" => isp = Some(read_str(buf, offset)?),
-                "is_public_proxy" => is_public_proxy = Some(read_bool(buf, offset)?),
-                "is_residential_proxy" => is_residential_proxy = Some(read_bool(buf, offset)?),
-                "is_satellite_provider" => is_satellite_provider = Some(read_bool(buf, offset)?),
-                "is_legitimate_proxy" => is_legitimate_proxy = Some(read_bool(buf, offset)?),
-                "static_ip_score" => static_ip_score = Some(read_f64(buf, offset)?),
-                "is_tor_exit_node" => is_tor_exit_node = Some(read_bool(buf, offset)?),
-                "mobile_country_code" => mobile_country_code = Some(read_str(buf, offset)?),
-                "mobile_network_code" => mobile_network_code = Some(read_str(buf, offset)?),
-                "organization" => organization = Some(read_str(buf, offset)?),
-                "user_type" => user_type = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::ConnectionType => connection_type = Some(read_str(buf, offset)?),
+                Key::Domain => domain = Some(read_str(buf, offset)?),
+                Key::IpAddress => ip_address = Some(read_str(buf, offset)?),
+                Key::IsAnonymous => is_anonymous = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousProxy => is_anonymous_proxy = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousVpn => is_anonymous_vpn = Some(read_bool(buf, offset)?),
+                Key::IsAnycast => is_anycast = Some(read_bool(buf, offset)?),
+                Key::IsHostingProvider => is_hosting_provider = Some(read_bool(buf, offset)?),
+                Key::Isp => isp = Some(read_str(buf, offset)?),
+                Key::IsPublicProxy => is_public_proxy = Some(read_bool(buf, offset)?),
+                Key::IsResidentialProxy => is_residential_proxy = Some(read_bool(buf, offset)?),
+                Key::IsSatelliteProvider => is_satellite_provider = Some(read_bool(buf, offset)?),
+                Key::IsLegitimateProxy => is_legitimate_proxy = Some(read_bool(buf, offset)?),
+                Key::StaticIpScore => static_ip_score = Some(read_f64(buf, offset)?),
+                Key::IsTorExitNode => is_tor_exit_node = Some(read_bool(buf, offset)?),
+                Key::MobileCountryCode => mobile_country_code = Some(read_str(buf, offset)?),
+                Key::MobileNetworkCode => mobile_network_code = Some(read_str(buf, offset)?),
+                Key::Network => network = Some(read_str(buf, offset)?),
+                Key::Organization => organization = Some(read_str(buf, offset)?),
+                Key::UserCount => user_count = Some(read_u32(buf, offset)?),
+                Key::UserType => user_type = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -436,6 +850,7 @@ impl<'a> Decoder<'a> for EnterpriseTraits<'a> {
             autonomous_system_organization,
             connection_type,
             domain,
+            ip_address,
             is_anonymous,
             is_anonymous_proxy,
             is_anonymous_vpn,
@@ -450,7 +865,9 @@ impl<'a> Decoder<'a> for EnterpriseTraits<'a> {
             is_tor_exit_node,
             mobile_country_code,
             mobile_network_code,
+            network,
             organization,
+            user_count,
             user_type,
         })
     }
@@ -0,0 +1,116 @@
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::Error;
+
+/// An IP network, like `81.2.69.0/24` or `2a02:ff80::/29`. Host bits of
+/// the address are always cleared.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Network {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Network {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, Error> {
+        let addr = match addr {
+            IpAddr::V4(addr) => {
+                if prefix_len > 32 {
+                    return Err(Error::InvalidNetwork);
+                }
+
+                let bits = u32::from(addr)
+                    .checked_shr(32 - prefix_len as u32)
+                    .unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(
+                    bits.checked_shl(32 - prefix_len as u32).unwrap_or(0),
+                ))
+            }
+            IpAddr::V6(addr) => {
+                if prefix_len > 128 {
+                    return Err(Error::InvalidNetwork);
+                }
+
+                let bits = u128::from(addr)
+                    .checked_shr(128 - prefix_len as u32)
+                    .unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(
+                    bits.checked_shl(128 - prefix_len as u32).unwrap_or(0),
+                ))
+            }
+        };
+
+        Ok(Network { addr, prefix_len })
+    }
+
+    /// Build a network from the address bits walked down the search tree,
+    /// `bits` are left aligned, IPv4 addresses take the 32 high bits.
+    pub(crate) fn from_bits(bits: u128, prefix_len: u8, ipv4: bool) -> Self {
+        let addr = if ipv4 {
+            IpAddr::V4(Ipv4Addr::from((bits >> 96) as u32))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(bits))
+        };
+
+        Network { addr, prefix_len }
+    }
+
+    /// The first address of the network.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+}
+
+impl FromStr for Network {
+    type Err = Error;
+
+    /// Parse `addr/prefix_len`, a bare address is a network of itself.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => {
+                let prefix_len = prefix_len.parse().map_err(|_| Error::InvalidNetwork)?;
+                (addr, Some(prefix_len))
+            }
+            None => (s, None),
+        };
+
+        let addr = IpAddr::from_str(addr).map_err(|_| Error::InvalidNetwork)?;
+        let prefix_len = prefix_len.unwrap_or(if addr.is_ipv4() { 32 } else { 128 });
+
+        Network::new(addr, prefix_len)
+    }
+}
+
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for (input, want) in [
+            ("81.2.69.142/24", "81.2.69.0/24"),
+            ("81.2.69.142", "81.2.69.142/32"),
+            ("0.0.0.0/0", "0.0.0.0/0"),
+            ("2a02:ff80::1/29", "2a02:ff80::/29"),
+            ("::/0", "::/0"),
+        ] {
+            let network = input.parse::<Network>().unwrap();
+            assert_eq!(network.to_string(), want);
+        }
+
+        assert!("81.2.69.142/33".parse::<Network>().is_err());
+        assert!("2a02:ff80::/129".parse::<Network>().is_err());
+        assert!("81.2.69/24".parse::<Network>().is_err());
+    }
+}
@@ -0,0 +1,874 @@
+//! Owned versions of the records, they don't borrow from the reader so they
+//! can be cached, sent to other threads or kept after the reader is dropped.
+//!
+//! Strings are shared `Arc<str>`, the [`Interner`] used by the conversion
+//! hands out the same allocation for equal strings. Names, ISO codes and
+//! time zones repeat a lot, so one interner should be kept for the whole
+//! cache.
+//!
+//! ```no_run
+//! use maxminddb::owned::{Interner, IntoOwned};
+//! use maxminddb::{City, Reader};
+//!
+//! let reader = Reader::open_file("GeoIP2-City.mmdb").unwrap();
+//! let mut interner = Interner::default();
+//!
+//! let city = reader
+//!     .lookup::<City>("81.2.69.142".parse().unwrap())
+//!     .unwrap()
+//!     .into_owned(&mut interner);
+//! drop(reader);
+//! println!("{:?}", city.city);
+//! ```
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Deduplicate strings, every call with an equal string returns a clone of
+/// the same `Arc<str>`.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(s) {
+            return Arc::clone(interned);
+        }
+
+        let interned: Arc<str> = Arc::from(s);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// Number of distinct strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Forget strings that are not used anymore, e.g. after cached records
+    /// have been evicted.
+    pub fn shrink(&mut self) {
+        self.strings.retain(|s| Arc::strong_count(s) > 1);
+    }
+}
+
+/// Conversion of a borrowed record to its owned counterpart.
+pub trait IntoOwned {
+    type Owned;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned;
+}
+
+impl IntoOwned for &str {
+    type Owned = Arc<str>;
+
+    #[inline]
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        interner.intern(self)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    #[inline]
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        self.map(|value| value.into_owned(interner))
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        self.into_iter()
+            .map(|value| value.into_owned(interner))
+            .collect()
+    }
+}
+
+impl<A: IntoOwned, B: IntoOwned> IntoOwned for (A, B) {
+    type Owned = (A::Owned, B::Owned);
+
+    #[inline]
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        (self.0.into_owned(interner), self.1.into_owned(interner))
+    }
+}
+
+/// GeoIP2 Anonymous Ip record, it borrows nothing already.
+pub type AnonymousIp = crate::AnonymousIp;
+
+impl IntoOwned for crate::AnonymousIp {
+    type Owned = AnonymousIp;
+
+    fn into_owned(self, _interner: &mut Interner) -> Self::Owned {
+        self
+    }
+}
+
+/// GeoIP2 IP Risk record, it borrows nothing already.
+pub type IpRisk = crate::IpRisk;
+
+impl IntoOwned for crate::IpRisk {
+    type Owned = IpRisk;
+
+    fn into_owned(self, _interner: &mut Interner) -> Self::Owned {
+        self
+    }
+}
+
+/// GeoIP2 Static IP Score record, it borrows nothing already.
+pub type StaticIpScore = crate::StaticIpScore;
+
+impl IntoOwned for crate::StaticIpScore {
+    type Owned = StaticIpScore;
+
+    fn into_owned(self, _interner: &mut Interner) -> Self::Owned {
+        self
+    }
+}
+
+/// GeoIP2 User Count record, it borrows nothing already.
+pub type UserCount = crate::UserCount;
+
+impl IntoOwned for crate::UserCount {
+    type Owned = UserCount;
+
+    fn into_owned(self, _interner: &mut Interner) -> Self::Owned {
+        self
+    }
+}
+
+/// GeoIP2 DensityIncome record, it borrows nothing already.
+pub type DensityIncome = crate::DensityIncome;
+
+impl IntoOwned for crate::DensityIncome {
+    type Owned = DensityIncome;
+
+    fn into_owned(self, _interner: &mut Interner) -> Self::Owned {
+        self
+    }
+}
+
+/// Owned GeoIP2 Country record
+#[derive(Clone, Debug, Default)]
+pub struct Country {
+    pub continent: Option<models::Continent>,
+    pub country: Option<models::Country>,
+    pub registered_country: Option<models::Country>,
+    pub represented_country: Option<models::RepresentedCountry>,
+    pub traits: Option<models::Traits>,
+}
+
+impl IntoOwned for crate::Country<'_> {
+    type Owned = Country;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        Country {
+            continent: self.continent.into_owned(interner),
+            country: self.country.into_owned(interner),
+            registered_country: self.registered_country.into_owned(interner),
+            represented_country: self.represented_country.into_owned(interner),
+            traits: self.traits.into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoIP2 City record
+#[derive(Clone, Debug, Default)]
+pub struct City {
+    pub city: Option<models::City>,
+    pub continent: Option<models::Continent>,
+    pub country: Option<models::Country>,
+    pub location: Option<models::Location>,
+    pub postal: Option<models::Postal>,
+    pub registered_country: Option<models::Country>,
+    pub represented_country: Option<models::RepresentedCountry>,
+    pub subdivisions: Option<Vec<models::Subdivision>>,
+    pub traits: Option<models::Traits>,
+}
+
+impl IntoOwned for crate::City<'_> {
+    type Owned = City;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        City {
+            city: self.city.into_owned(interner),
+            continent: self.continent.into_owned(interner),
+            country: self.country.into_owned(interner),
+            location: self.location.into_owned(interner),
+            postal: self.postal.into_owned(interner),
+            registered_country: self.registered_country.into_owned(interner),
+            represented_country: self.represented_country.into_owned(interner),
+            subdivisions: self.subdivisions.into_owned(interner),
+            traits: self.traits.into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoIP2 Enterprise record
+#[derive(Clone, Debug, Default)]
+pub struct Enterprise {
+    pub continent: Option<models::Continent>,
+    pub country: Option<models::EnterpriseCountry>,
+    pub subdivisions: Option<Vec<models::EnterpriseSubdivision>>,
+    pub city: Option<models::EnterpriseCity>,
+    pub location: Option<models::Location>,
+    pub postal: Option<models::EnterprisePostal>,
+    pub registered_country: Option<models::EnterpriseCountry>,
+    pub represented_country: Option<models::EnterpriseRepresentedCountry>,
+    pub traits: Option<models::EnterpriseTraits>,
+}
+
+impl IntoOwned for crate::Enterprise<'_> {
+    type Owned = Enterprise;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        Enterprise {
+            continent: self.continent.into_owned(interner),
+            country: self.country.into_owned(interner),
+            subdivisions: self.subdivisions.into_owned(interner),
+            city: self.city.into_owned(interner),
+            location: self.location.into_owned(interner),
+            postal: self.postal.into_owned(interner),
+            registered_country: self.registered_country.into_owned(interner),
+            represented_country: self.represented_country.into_owned(interner),
+            traits: self.traits.into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoIP2 Connection-Type record
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionType {
+    pub connection_type: Option<Arc<str>>,
+}
+
+impl ConnectionType {
+    pub fn connection_kind(&self) -> Option<crate::models::ConnectionKind<'_>> {
+        self.connection_type
+            .as_deref()
+            .map(crate::models::ConnectionKind::from)
+    }
+}
+
+impl IntoOwned for crate::ConnectionType<'_> {
+    type Owned = ConnectionType;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        ConnectionType {
+            connection_type: self.connection_type.into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoIP2 Domain record
+#[derive(Clone, Debug, Default)]
+pub struct Domain {
+    pub domain: Option<Arc<str>>,
+}
+
+impl IntoOwned for crate::Domain<'_> {
+    type Owned = Domain;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        Domain {
+            domain: self.domain.into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoIP2 ISP record
+#[derive(Clone, Debug, Default)]
+pub struct Isp {
+    pub autonomous_system_number: Option<u32>,
+    pub autonomous_system_organization: Option<Arc<str>>,
+    pub isp: Option<Arc<str>>,
+    pub mobile_country_code: Option<Arc<str>>,
+    pub mobile_network_code: Option<Arc<str>>,
+    pub organization: Option<Arc<str>>,
+}
+
+impl IntoOwned for crate::Isp<'_> {
+    type Owned = Isp;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        Isp {
+            autonomous_system_number: self.autonomous_system_number,
+            autonomous_system_organization: self
+                .autonomous_system_organization
+                .into_owned(interner),
+            isp: self.isp.into_owned(interner),
+            mobile_country_code: self.mobile_country_code.into_owned(interner),
+            mobile_network_code: self.mobile_network_code.into_owned(interner),
+            organization: self.organization.into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoLite2 ASN record
+#[derive(Clone, Debug, Default)]
+pub struct Asn {
+    pub autonomous_system_number: Option<u32>,
+    pub autonomous_system_organization: Option<Arc<str>>,
+}
+
+impl IntoOwned for crate::Asn<'_> {
+    type Owned = Asn;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        Asn {
+            autonomous_system_number: self.autonomous_system_number,
+            autonomous_system_organization: self
+                .autonomous_system_organization
+                .into_owned(interner),
+        }
+    }
+}
+
+/// Owned GeoIP Anonymous Plus record
+#[derive(Clone, Debug, Default)]
+pub struct AnonymousPlus {
+    pub anonymizer_confidence: Option<u16>,
+    pub is_anonymous: Option<bool>,
+    pub is_anonymous_vpn: Option<bool>,
+    pub is_hosting_provider: Option<bool>,
+    pub is_public_proxy: Option<bool>,
+    pub is_residential_proxy: Option<bool>,
+    pub is_tor_exit_node: Option<bool>,
+    pub network_last_seen: Option<Arc<str>>,
+    pub provider_name: Option<Arc<str>>,
+}
+
+impl IntoOwned for crate::AnonymousPlus<'_> {
+    type Owned = AnonymousPlus;
+
+    fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+        AnonymousPlus {
+            anonymizer_confidence: self.anonymizer_confidence,
+            is_anonymous: self.is_anonymous,
+            is_anonymous_vpn: self.is_anonymous_vpn,
+            is_hosting_provider: self.is_hosting_provider,
+            is_public_proxy: self.is_public_proxy,
+            is_residential_proxy: self.is_residential_proxy,
+            is_tor_exit_node: self.is_tor_exit_node,
+            network_last_seen: self.network_last_seen.into_owned(interner),
+            provider_name: self.provider_name.into_owned(interner),
+        }
+    }
+}
+
+/// Owned counterparts of [`crate::models`].
+pub mod models {
+    use std::sync::Arc;
+
+    use super::{Interner, IntoOwned};
+    use crate::models::{ConnectionKind, RepresentationKind, UserKind};
+
+    /// Owned localized names, see [`crate::models::Names`].
+    #[derive(Clone, Debug, Default, PartialEq)]
+    pub struct Names(Vec<(Arc<str>, Arc<str>)>);
+
+    impl Names {
+        pub fn get(&self, language: &str) -> Option<&Arc<str>> {
+            self.0
+                .iter()
+                .find(|(locale, _)| &**locale == language)
+                .map(|(_, name)| name)
+        }
+
+        pub fn best<L: AsRef<str>>(&self, languages: &[L]) -> Option<&Arc<str>> {
+            languages
+                .iter()
+                .find_map(|language| self.get(language.as_ref()))
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = (&Arc<str>, &Arc<str>)> {
+            self.0.iter().map(|(locale, name)| (locale, name))
+        }
+
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    impl IntoOwned for crate::models::Names<'_> {
+        type Owned = Names;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Names(
+                self.iter()
+                    .map(|(locale, name)| (interner.intern(locale), interner.intern(name)))
+                    .collect(),
+            )
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct City {
+        pub confidence: Option<u16>,
+        pub geoname_id: Option<u32>,
+        pub names: Option<Names>,
+    }
+
+    impl IntoOwned for crate::models::City<'_> {
+        type Owned = City;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            City {
+                confidence: self.confidence,
+                geoname_id: self.geoname_id,
+                names: self.names.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Continent {
+        pub geoname_id: Option<u32>,
+        pub code: Option<Arc<str>>,
+        pub names: Option<Names>,
+    }
+
+    impl IntoOwned for crate::models::Continent<'_> {
+        type Owned = Continent;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Continent {
+                geoname_id: self.geoname_id,
+                code: self.code.into_owned(interner),
+                names: self.names.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Country {
+        pub confidence: Option<u16>,
+        pub geoname_id: Option<u32>,
+        pub is_in_european_union: Option<bool>,
+        pub iso_code: Option<Arc<str>>,
+        pub names: Option<Names>,
+    }
+
+    impl IntoOwned for crate::models::Country<'_> {
+        type Owned = Country;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Country {
+                confidence: self.confidence,
+                geoname_id: self.geoname_id,
+                is_in_european_union: self.is_in_european_union,
+                iso_code: self.iso_code.into_owned(interner),
+                names: self.names.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct RepresentedCountry {
+        pub geoname_id: Option<u32>,
+        pub is_in_european_union: Option<bool>,
+        pub iso_code: Option<Arc<str>>,
+        pub names: Option<Names>,
+        pub representation_type: Option<Arc<str>>,
+    }
+
+    impl RepresentedCountry {
+        pub fn representation_kind(&self) -> Option<RepresentationKind<'_>> {
+            self.representation_type
+                .as_deref()
+                .map(RepresentationKind::from)
+        }
+    }
+
+    impl IntoOwned for crate::models::RepresentedCountry<'_> {
+        type Owned = RepresentedCountry;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            RepresentedCountry {
+                geoname_id: self.geoname_id,
+                is_in_european_union: self.is_in_european_union,
+                iso_code: self.iso_code.into_owned(interner),
+                names: self.names.into_owned(interner),
+                representation_type: self.representation_type.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Traits {
+        pub autonomous_system_number: Option<u32>,
+        pub autonomous_system_organization: Option<Arc<str>>,
+        pub connection_type: Option<Arc<str>>,
+        pub domain: Option<Arc<str>>,
+        pub ip_address: Option<Arc<str>>,
+        pub is_anonymous: Option<bool>,
+        pub is_anonymous_proxy: Option<bool>,
+        pub is_anonymous_vpn: Option<bool>,
+        pub is_anycast: Option<bool>,
+        pub is_hosting_provider: Option<bool>,
+        pub is_legitimate_proxy: Option<bool>,
+        pub is_public_proxy: Option<bool>,
+        pub is_residential_proxy: Option<bool>,
+        pub is_satellite_provider: Option<bool>,
+        pub is_tor_exit_node: Option<bool>,
+        pub isp: Option<Arc<str>>,
+        pub mobile_country_code: Option<Arc<str>>,
+        pub mobile_network_code: Option<Arc<str>>,
+        pub network: Option<Arc<str>>,
+        pub organization: Option<Arc<str>>,
+        pub static_ip_score: Option<f64>,
+        pub user_count: Option<u32>,
+        pub user_type: Option<Arc<str>>,
+    }
+
+    impl Traits {
+        pub fn connection_kind(&self) -> Option<ConnectionKind<'_>> {
+            self.connection_type.as_deref().map(ConnectionKind::from)
+        }
+
+        pub fn user_kind(&self) -> Option<UserKind<'_>> {
+            self.user_type.as_deref().map(UserKind::from)
+        }
+    }
+
+    impl IntoOwned for crate::models::Traits<'_> {
+        type Owned = Traits;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Traits {
+                autonomous_system_number: self.autonomous_system_number,
+                autonomous_system_organization: self
+                    .autonomous_system_organization
+                    .into_owned(interner),
+                connection_type: self.connection_type.into_owned(interner),
+                domain: self.domain.into_owned(interner),
+                ip_address: self.ip_address.into_owned(interner),
+                is_anonymous: self.is_anonymous,
+                is_anonymous_proxy: self.is_anonymous_proxy,
+                is_anonymous_vpn: self.is_anonymous_vpn,
+                is_anycast: self.is_anycast,
+                is_hosting_provider: self.is_hosting_provider,
+                is_legitimate_proxy: self.is_legitimate_proxy,
+                is_public_proxy: self.is_public_proxy,
+                is_residential_proxy: self.is_residential_proxy,
+                is_satellite_provider: self.is_satellite_provider,
+                is_tor_exit_node: self.is_tor_exit_node,
+                isp: self.isp.into_owned(interner),
+                mobile_country_code: self.mobile_country_code.into_owned(interner),
+                mobile_network_code: self.mobile_network_code.into_owned(interner),
+                network: self.network.into_owned(interner),
+                organization: self.organization.into_owned(interner),
+                static_ip_score: self.static_ip_score,
+                user_count: self.user_count,
+                user_type: self.user_type.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Location {
+        pub latitude: Option<f64>,
+        pub longitude: Option<f64>,
+        pub accuracy_radius: Option<u16>,
+        pub time_zone: Option<Arc<str>>,
+        pub metro_code: Option<u16>,
+        pub average_income: Option<u32>,
+        pub population_density: Option<u32>,
+    }
+
+    impl IntoOwned for crate::models::Location<'_> {
+        type Owned = Location;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Location {
+                latitude: self.latitude,
+                longitude: self.longitude,
+                accuracy_radius: self.accuracy_radius,
+                time_zone: self.time_zone.into_owned(interner),
+                metro_code: self.metro_code,
+                average_income: self.average_income,
+                population_density: self.population_density,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Postal {
+        pub code: Option<Arc<str>>,
+        pub confidence: Option<u16>,
+    }
+
+    impl IntoOwned for crate::models::Postal<'_> {
+        type Owned = Postal;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Postal {
+                code: self.code.into_owned(interner),
+                confidence: self.confidence,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct Subdivision {
+        pub confidence: Option<u16>,
+        pub geoname_id: Option<u32>,
+        pub iso_code: Option<Arc<str>>,
+        pub names: Option<Names>,
+    }
+
+    impl IntoOwned for crate::models::Subdivision<'_> {
+        type Owned = Subdivision;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            Subdivision {
+                confidence: self.confidence,
+                geoname_id: self.geoname_id,
+                iso_code: self.iso_code.into_owned(interner),
+                names: self.names.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct EnterpriseCountry {
+        pub geoname_id: Option<u32>,
+        pub iso_code: Option<Arc<str>>,
+        pub names: Option<Names>,
+        pub is_in_european_union: Option<bool>,
+        pub confidence: Option<u16>,
+    }
+
+    impl IntoOwned for crate::models::EnterpriseCountry<'_> {
+        type Owned = EnterpriseCountry;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            EnterpriseCountry {
+                geoname_id: self.geoname_id,
+                iso_code: self.iso_code.into_owned(interner),
+                names: self.names.into_owned(interner),
+                is_in_european_union: self.is_in_european_union,
+                confidence: self.confidence,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct EnterpriseRepresentedCountry {
+        pub confidence: Option<u16>,
+        pub geoname_id: Option<u32>,
+        pub iso_code: Option<Arc<str>>,
+        pub names: Option<Names>,
+        pub is_in_european_union: Option<bool>,
+        pub country_type: Option<Arc<str>>,
+    }
+
+    impl EnterpriseRepresentedCountry {
+        pub fn representation_kind(&self) -> Option<RepresentationKind<'_>> {
+            self.country_type.as_deref().map(RepresentationKind::from)
+        }
+    }
+
+    impl IntoOwned for crate::models::EnterpriseRepresentedCountry<'_> {
+        type Owned = EnterpriseRepresentedCountry;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            EnterpriseRepresentedCountry {
+                confidence: self.confidence,
+                geoname_id: self.geoname_id,
+                iso_code: self.iso_code.into_owned(interner),
+                names: self.names.into_owned(interner),
+                is_in_european_union: self.is_in_european_union,
+                country_type: self.country_type.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct EnterpriseCity {
+        pub confidence: Option<u16>,
+        pub geoname_id: Option<u32>,
+        pub names: Option<Names>,
+    }
+
+    impl IntoOwned for crate::models::EnterpriseCity<'_> {
+        type Owned = EnterpriseCity;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            EnterpriseCity {
+                confidence: self.confidence,
+                geoname_id: self.geoname_id,
+                names: self.names.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct EnterprisePostal {
+        pub confidence: Option<u16>,
+        pub code: Option<Arc<str>>,
+    }
+
+    impl IntoOwned for crate::models::EnterprisePostal<'_> {
+        type Owned = EnterprisePostal;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            EnterprisePostal {
+                confidence: self.confidence,
+                code: self.code.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct EnterpriseSubdivision {
+        pub confidence: Option<u16>,
+        pub geoname_id: Option<u32>,
+        pub iso_code: Option<Arc<str>>,
+        pub names: Option<Names>,
+    }
+
+    impl IntoOwned for crate::models::EnterpriseSubdivision<'_> {
+        type Owned = EnterpriseSubdivision;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            EnterpriseSubdivision {
+                confidence: self.confidence,
+                geoname_id: self.geoname_id,
+                iso_code: self.iso_code.into_owned(interner),
+                names: self.names.into_owned(interner),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    pub struct EnterpriseTraits {
+        pub autonomous_system_number: Option<u32>,
+        pub autonomous_system_organization: Option<Arc<str>>,
+        pub connection_type: Option<Arc<str>>,
+        pub domain: Option<Arc<str>>,
+        pub ip_address: Option<Arc<str>>,
+        pub is_anonymous: Option<bool>,
+        pub is_anonymous_proxy: Option<bool>,
+        pub is_anonymous_vpn: Option<bool>,
+        pub is_anycast: Option<bool>,
+        pub is_hosting_provider: Option<bool>,
+        pub isp: Option<Arc<str>>,
+        pub is_public_proxy: Option<bool>,
+        pub is_residential_proxy: Option<bool>,
+        pub is_satellite_provider: Option<bool>,
+        pub is_legitimate_proxy: Option<bool>,
+        pub static_ip_score: Option<f64>,
+        pub is_tor_exit_node: Option<bool>,
+        pub mobile_country_code: Option<Arc<str>>,
+        pub mobile_network_code: Option<Arc<str>>,
+        pub network: Option<Arc<str>>,
+        pub organization: Option<Arc<str>>,
+        pub user_count: Option<u32>,
+        pub user_type: Option<Arc<str>>,
+    }
+
+    impl EnterpriseTraits {
+        pub fn connection_kind(&self) -> Option<ConnectionKind<'_>> {
+            self.connection_type.as_deref().map(ConnectionKind::from)
+        }
+
+        pub fn user_kind(&self) -> Option<UserKind<'_>> {
+            self.user_type.as_deref().map(UserKind::from)
+        }
+    }
+
+    impl IntoOwned for crate::models::EnterpriseTraits<'_> {
+        type Owned = EnterpriseTraits;
+
+        fn into_owned(self, interner: &mut Interner) -> Self::Owned {
+            EnterpriseTraits {
+                autonomous_system_number: self.autonomous_system_number,
+                autonomous_system_organization: self
+                    .autonomous_system_organization
+                    .into_owned(interner),
+                connection_type: self.connection_type.into_owned(interner),
+                domain: self.domain.into_owned(interner),
+                ip_address: self.ip_address.into_owned(interner),
+                is_anonymous: self.is_anonymous,
+                is_anonymous_proxy: self.is_anonymous_proxy,
+                is_anonymous_vpn: self.is_anonymous_vpn,
+                is_anycast: self.is_anycast,
+                is_hosting_provider: self.is_hosting_provider,
+                isp: self.isp.into_owned(interner),
+                is_public_proxy: self.is_public_proxy,
+                is_residential_proxy: self.is_residential_proxy,
+                is_satellite_provider: self.is_satellite_provider,
+                is_legitimate_proxy: self.is_legitimate_proxy,
+                static_ip_score: self.static_ip_score,
+                is_tor_exit_node: self.is_tor_exit_node,
+                mobile_country_code: self.mobile_country_code.into_owned(interner),
+                mobile_network_code: self.mobile_network_code.into_owned(interner),
+                network: self.network.into_owned(interner),
+                organization: self.organization.into_owned(interner),
+                user_count: self.user_count,
+                user_type: self.user_type.into_owned(interner),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    #[test]
+    fn interned() {
+        let mut interner = Interner::default();
+        let reader = Reader::open_file("testdata/GeoIP2-City-Test.mmdb").unwrap();
+
+        let records = ["81.2.69.142", "81.2.69.160"]
+            .into_iter()
+            .map(|ip| {
+                reader
+                    .lookup::<crate::City>(ip.parse().unwrap())
+                    .unwrap()
+                    .into_owned(&mut interner)
+            })
+            .collect::<Vec<_>>();
+        drop(reader);
+
+        let codes = records
+            .iter()
+            .map(|record| record.country.as_ref().unwrap().iso_code.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(&*codes[0], "GB");
+        assert!(Arc::ptr_eq(&codes[0], &codes[1]));
+
+        // the interner, both records and `codes` hold the same allocation
+        assert_eq!(Arc::strong_count(&codes[0]), 5);
+        drop(codes);
+        drop(records);
+        let len = interner.len();
+        interner.shrink();
+        assert!(interner.len() < len);
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn send() {
+        fn assert_send<T: Send + Sync + 'static>() {}
+        assert_send::<City>();
+        assert_send::<Enterprise>();
+        assert_send::<Isp>();
+    }
+}
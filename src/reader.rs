@@ -1,25 +1,42 @@
 use std::net::IpAddr;
 use std::path::Path;
 
+use crate::builder::{BlockedTree, JumpTable, RangeTable};
 use crate::decode::{
-    bytes_to_usize, bytes_to_usize_with_prefix, read_bool, read_control, read_pointer, read_str,
-    read_usize, Decoder, DATA_TYPE_MAP, DATA_TYPE_POINTER, DATA_TYPE_SLICE,
+    is_value_start, read_bool, read_control, read_f64, read_key, read_str, read_u16, read_u32,
+    verify_values, Data, Decoder, DATA_TYPE_MAP,
 };
-use crate::metadata::{find_metadata_start, Metadata};
+use crate::key::{Key, KeyCache};
+use crate::metadata::{find_metadata_start, Metadata, METADATA_START_MARKER};
 use crate::{models, Error};
 
-const DATA_SECTION_SEPARATOR_SIZE: usize = 16;
+pub(crate) const DATA_SECTION_SEPARATOR_SIZE: usize = 16;
 
 /// A reader for the MaxMind DB format. The lifetime 'data' is tied to the lifetime
 /// of the underlying buffer holding the content of the database file.
 pub struct Reader<S: AsRef<[u8]>> {
-    data: S,
+    pub(crate) data: S,
+
+    pub(crate) search_tree_size: usize,
+    pub(crate) data_section_end: usize,
+    pub(crate) record_size: usize,
+    pub(crate) node_count: usize,
+    pub(crate) node_offset_multi: usize,
+    pub(crate) ip_v4_start: usize,
+
+    languages: Vec<String>,
+    verified: Option<Verified>,
+    keys: KeyCache,
+    pub(crate) jump_table: Option<JumpTable>,
+    pub(crate) blocked_tree: Option<BlockedTree>,
+    pub(crate) ipv4_ranges: Option<RangeTable>,
+}
 
-    search_tree_size: usize,
-    record_size: usize,
-    node_count: usize,
-    node_offset_multi: usize,
-    ip_v4_start: usize,
+/// What [`Reader::verify`] found: where each value of the data section
+/// starts, and where that data section was.
+struct Verified {
+    starts: Vec<u64>,
+    section: (usize, usize),
 }
 
 impl Reader<Vec<u8>> {
@@ -50,19 +67,37 @@ impl<'a, S: AsRef<[u8]>> Reader<S> {
         let node_count = metadata.node_count;
         let ip_version = metadata.ip_version;
         let node_offset_multi = record_size / 4;
-        let search_tree_size = node_count * node_offset_multi;
-        let data_section_start = search_tree_size + DATA_SECTION_SEPARATOR_SIZE;
-        if data_section_start > metadata_start {
-            return Err(Error::InvalidSearchTreeSize);
-        }
+        let data_section_start = node_count
+            .checked_mul(node_offset_multi)
+            .and_then(|size| size.checked_add(DATA_SECTION_SEPARATOR_SIZE));
+        let data_section_end = metadata_start - METADATA_START_MARKER.len();
+        let search_tree_size = match data_section_start {
+            Some(start) if start <= data_section_end => start - DATA_SECTION_SEPARATOR_SIZE,
+            _ => return Err(Error::InvalidSearchTreeSize),
+        };
+
+        // english if available, the names of non-GeoIP databases are
+        // unlikely to be localized anyway
+        let languages = match metadata.languages.first() {
+            Some(_) if metadata.languages.contains(&"en") => vec!["en".to_string()],
+            Some(first) => vec![first.to_string()],
+            None => vec![],
+        };
 
         let mut reader = Reader {
             data: buf,
             search_tree_size,
+            data_section_end,
             record_size,
             node_count,
             node_offset_multi,
             ip_v4_start: 0,
+            languages,
+            verified: None,
+            keys: KeyCache::default(),
+            jump_table: None,
+            blocked_tree: None,
+            ipv4_ranges: None,
         };
 
         if ip_version == 6 {
@@ -91,8 +126,106 @@ impl<'a, S: AsRef<[u8]>> Reader<S> {
         Metadata::from_bytes(&buf[offset..])
     }
 
-    /// Lookup the socket address in the opened MaxMind DB
+    /// The preferred languages of names, see [`City::display_name`].
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    /// Set the preferred languages of names, most preferred first. Every
+    /// language must be one of the `languages` of the metadata, or a
+    /// regional variant of one, like `zh-CN` when the database lists `zh`.
+    pub fn set_languages<L: AsRef<str>>(&mut self, languages: &[L]) -> Result<(), Error> {
+        let metadata = self.metadata()?;
+        if let Some(unknown) = languages.iter().find(|language| {
+            let language = language.as_ref();
+            let primary = language.split('-').next().unwrap_or(language);
+            !metadata
+                .languages
+                .iter()
+                .any(|known| *known == language || *known == primary)
+        }) {
+            return Err(Error::UnknownLanguage(unknown.as_ref().to_string()));
+        }
+
+        self.languages = languages.iter().map(|l| l.as_ref().to_string()).collect();
+        Ok(())
+    }
+
+    /// Check the whole database once: every string of the data section
+    /// must be UTF-8, and every pointer and record of the search tree must
+    /// point at the start of a value. Strings read at those starts are then
+    /// not validated again, at the cost of keeping one bit per byte of the
+    /// data section.
+    pub fn verify(&mut self) -> Result<(), Error> {
+        let starts = verify_values(self.data_section())?;
+        for node in 0..self.node_count {
+            for index in 0..2 {
+                let record = self.read_node(node, index);
+                if record > self.node_count
+                    && !is_value_start(&starts, self.resolve_data_pointer(record)?)
+                {
+                    return Err(Error::CorruptSearchTree);
+                }
+            }
+        }
+
+        let data = self.data_section();
+        self.verified = Some(Verified {
+            starts,
+            section: (data.as_ptr() as usize, data.len()),
+        });
+        Ok(())
+    }
+
+    /// Whether [`Reader::verify`] succeeded.
+    pub fn is_verified(&self) -> bool {
+        self.verified.is_some()
+    }
+
+    /// Lookup the socket address in the opened MaxMind DB. Records are
+    /// usually maps, but can be any value, like an `u32` or a `&str` in a
+    /// database of just ASNs or country codes.
     pub fn lookup<T: Decoder<'a>>(&'a self, addr: IpAddr) -> Result<T, Error> {
+        let mut offset = self.find_value(addr)?;
+        T::decode(self.data(), &mut offset)
+    }
+
+    /// Lookup the address and decode the record into `record`, reusing the
+    /// buffers it holds from a previous lookup. With a warmed up `City`,
+    /// lookups don't allocate. Its `subdivisions` are then `Some` and empty
+    /// for a record without any, to keep their buffer.
+    pub fn lookup_into<T: Decoder<'a>>(
+        &'a self,
+        record: &mut T,
+        addr: IpAddr,
+    ) -> Result<(), Error> {
+        let start = self.find_value(addr)?;
+        let buf = self.data();
+        let mut offset = start;
+        match read_control(buf.bytes(), &mut offset)? {
+            (DATA_TYPE_MAP, size) => record.decode_into(buf, &mut offset, size),
+            _ => record.decode_in_place(buf, &mut start.clone()),
+        }
+    }
+
+    /// Find the record of `addr`, returns the offset of its first key and
+    /// the size of the map.
+    #[inline]
+    pub(crate) fn find_record(&self, addr: IpAddr) -> Result<(usize, usize), Error> {
+        let mut offset = self.find_value(addr)?;
+
+        // the record must be a MAP
+        let (data_type, size) = read_control(self.data_section(), &mut offset)?;
+        if data_type != DATA_TYPE_MAP {
+            return Err(Error::InvalidDataType(data_type));
+        }
+
+        Ok((offset, size))
+    }
+
+    /// Find the record of `addr`, returns the offset of its value.
+    #[inline]
+    pub(crate) fn find_value(&self, addr: IpAddr) -> Result<usize, Error> {
         let pointer = match addr {
             IpAddr::V4(addr) => self.find_address_in_tree(&addr.octets())?,
             IpAddr::V6(addr) => {
@@ -107,82 +240,157 @@ impl<'a, S: AsRef<[u8]>> Reader<S> {
             return Err(Error::AddressNotFound);
         }
 
-        let mut offset = pointer - self.node_count - DATA_SECTION_SEPARATOR_SIZE;
-        let buf = self.data.as_ref();
-        if offset >= buf.len() {
-            return Err(Error::CorruptSearchTree);
+        self.resolve_data_pointer(pointer)
+    }
+
+    pub(crate) fn find_address_in_tree(&self, ip: &[u8]) -> Result<usize, Error> {
+        if let (Some(ranges), Ok(ip)) = (&self.ipv4_ranges, <[u8; 4]>::try_from(ip)) {
+            return Ok(ranges.find(u32::from_be_bytes(ip)));
         }
 
-        // `T` must be a MAP
-        let buf = &buf[self.search_tree_size + DATA_SECTION_SEPARATOR_SIZE..];
-        let (data_type, size) = read_control(buf, &mut offset)?;
-        if data_type != DATA_TYPE_MAP {
-            return Err(Error::InvalidDataType(data_type));
+        // dispatch once per lookup, so the walk is specialized per record size
+        let node = match (&self.blocked_tree, self.record_size) {
+            (Some(tree), _) => self.walk_blocked_tree(tree, ip),
+            (None, 24) => self.walk_tree::<24>(ip)?,
+            (None, 28) => self.walk_tree::<28>(ip)?,
+            (None, 32) => self.walk_tree::<32>(ip)?,
+            // record_size is validated at the very beginning
+            _ => unreachable!(),
+        };
+
+        if self.node_count == node {
+            Ok(0)
+        } else if node > self.node_count {
+            Ok(node)
+        } else {
+            Err(Error::InvalidNode)
+        }
+    }
+
+    #[inline(always)]
+    fn walk_tree<const RECORD_SIZE: usize>(&self, ip: &[u8]) -> Result<usize, Error> {
+        let (mut node, skipped) = self.walk_start(ip, self.ip_v4_start);
+
+        let tree = &self.data.as_ref()[..self.search_tree_size];
+        if self.node_count.checked_mul(RECORD_SIZE / 4) != Some(tree.len()) {
+            return Err(Error::InvalidSearchTreeSize);
+        }
+        for i in skipped..ip.len() * 8 {
+            if node >= self.node_count {
+                break;
+            }
+
+            let bit = 1 & (ip[i >> 3] >> (7 - (i % 8)));
+            // SAFETY: the tree holds exactly `node_count` nodes
+            node = unsafe { read_record::<RECORD_SIZE>(tree, node, bit as usize) };
         }
 
-        T::decode_with_size(buf, &mut offset, size)
+        Ok(node)
     }
 
-    fn find_address_in_tree(&self, ip: &[u8]) -> Result<usize, Error> {
-        let bit_count = ip.len() * 8;
-        let mut node: usize = if bit_count == 128 {
-            0
-        } else {
-            self.ip_v4_start
-        };
+    #[inline]
+    fn walk_blocked_tree(&self, tree: &BlockedTree, ip: &[u8]) -> usize {
+        let (mut node, skipped) = self.walk_start(ip, tree.ip_v4_start);
 
-        // node buf
-        for i in 0..bit_count {
+        for i in skipped..ip.len() * 8 {
             if node >= self.node_count {
                 break;
             }
 
             let bit = 1 & (ip[i >> 3] >> (7 - (i % 8)));
-            node = self.read_node(node, bit as usize);
+            node = tree.nodes[node][bit as usize] as usize;
         }
 
-        if self.node_count == node {
-            Ok(0)
-        } else if node > self.node_count {
-            Ok(node)
-        } else {
-            Err(Error::InvalidNode)
+        node
+    }
+
+    /// The node to start walking `ip` from, and the number of bits of `ip`
+    /// already walked to reach it.
+    #[inline(always)]
+    fn walk_start(&self, ip: &[u8], ip_v4_start: usize) -> (usize, usize) {
+        match self.jump_table.as_ref().and_then(|t| t.start(ip)) {
+            Some(start) => start,
+            None if ip.len() == 16 => (0, 0),
+            None => (ip_v4_start, 0),
         }
     }
 
+    /// The data section, with the key cache of the reader for decoders.
     #[inline]
-    fn read_node(&self, node: usize, index: usize) -> usize {
-        let buf = self.data.as_ref();
+    pub(crate) fn data(&self) -> Data<'_> {
+        let data = self.data_section();
+        // only the bytes that were verified, whatever `S::as_ref` returns
+        let starts = self
+            .verified
+            .as_ref()
+            .filter(|verified| verified.section == (data.as_ptr() as usize, data.len()))
+            .map(|verified| &verified.starts[..]);
+        Data::of_reader(data, &self.keys, starts)
+    }
+
+    /// The data section, offsets of records and pointers are relative to it.
+    #[inline]
+    pub(crate) fn data_section(&self) -> &[u8] {
+        &self.data.as_ref()
+            [self.search_tree_size + DATA_SECTION_SEPARATOR_SIZE..self.data_section_end]
+    }
+
+    /// Convert a record value pointing past the search tree to an offset
+    /// in the data section.
+    #[inline]
+    pub(crate) fn resolve_data_pointer(&self, pointer: usize) -> Result<usize, Error> {
+        match pointer.checked_sub(self.node_count + DATA_SECTION_SEPARATOR_SIZE) {
+            Some(offset) if offset < self.data_section().len() => Ok(offset),
+            _ => Err(Error::CorruptSearchTree),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn read_node(&self, node: usize, index: usize) -> usize {
         let base = node * self.node_offset_multi;
+        let bytes =
+            &self.data.as_ref()[..self.search_tree_size][base..base + self.node_offset_multi];
 
+        // SAFETY: `bytes` is exactly one node
         match self.record_size {
-            28 => {
-                let mut middle = buf[base + 3];
-                if index != 0 {
-                    middle &= 0x0F
-                } else {
-                    middle = (0xF0 & middle) >> 4
-                }
+            24 => unsafe { read_record::<24>(bytes, 0, index) },
+            28 => unsafe { read_record::<28>(bytes, 0, index) },
+            32 => unsafe { read_record::<32>(bytes, 0, index) },
+            // record_size is validated at the very beginning
+            _ => unreachable!(),
+        }
+    }
+}
 
-                let offset = base + index * 4;
-                bytes_to_usize_with_prefix(middle as usize, &buf[offset..offset + 3])
-            }
-            24 => {
-                let offset = base + index * 3;
-                bytes_to_usize(&buf[offset..offset + 3])
-            }
-            32 => {
-                let offset = base + index * 4;
-                bytes_to_usize(&buf[offset..offset + 4])
+/// Read the record `index` of `node`, with fixed width loads.
+///
+/// # Safety
+///
+/// `tree` must hold at least `node + 1` nodes of `RECORD_SIZE` bits records.
+#[inline(always)]
+unsafe fn read_record<const RECORD_SIZE: usize>(tree: &[u8], node: usize, index: usize) -> usize {
+    let base = tree.as_ptr().add(node * RECORD_SIZE / 4);
+    match RECORD_SIZE {
+        24 => {
+            let record = base.add(index * 3);
+            u32::from_be_bytes([0, *record, *record.add(1), *record.add(2)]) as usize
+        }
+        // the middle byte holds the high nibble of both records
+        28 => {
+            if index == 0 {
+                let value = u32::from_be_bytes(base.cast::<[u8; 4]>().read_unaligned());
+                ((value & 0xF0) << 20 | value >> 8) as usize
+            } else {
+                let value = u32::from_be_bytes(base.add(3).cast::<[u8; 4]>().read_unaligned());
+                (value & 0x0FFF_FFFF) as usize
             }
-            // record_size is validated at the very beginning
-            _ => panic!(),
         }
+        _ => u32::from_be_bytes(base.add(index * 4).cast::<[u8; 4]>().read_unaligned()) as usize,
     }
 }
 
 /// GeoIP2 Anonymous Ip record
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AnonymousIp {
     pub is_anonymous: Option<bool>,
     pub is_anonymous_vpn: Option<bool>,
@@ -193,7 +401,7 @@ pub struct AnonymousIp {
 }
 
 impl<'a> Decoder<'a> for AnonymousIp {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut is_anonymous = None;
         let mut is_anonymous_vpn = None;
         let mut is_hosting_provider = None;
@@ -202,14 +410,14 @@ impl<'a> Decoder<'a> for AnonymousIp {
         let mut is_tor_exit_node = None;
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "is_anonymous" => is_anonymous = Some(read_bool(buf, offset)?),
-                "is_anonymous_vpn" => is_anonymous_vpn = Some(read_bool(buf, offset)?),
-                "is_hosting_provider" => is_hosting_provider = Some(read_bool(buf, offset)?),
-                "is_public_proxy" => is_public_proxy = Some(read_bool(buf, offset)?),
-                "is_residential_proxy" => is_residential_proxy = Some(read_bool(buf, offset)?),
-                "is_tor_exit_node" => is_tor_exit_node = Some(read_bool(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::IsAnonymous => is_anonymous = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousVpn => is_anonymous_vpn = Some(read_bool(buf, offset)?),
+                Key::IsHostingProvider => is_hosting_provider = Some(read_bool(buf, offset)?),
+                Key::IsPublicProxy => is_public_proxy = Some(read_bool(buf, offset)?),
+                Key::IsResidentialProxy => is_residential_proxy = Some(read_bool(buf, offset)?),
+                Key::IsTorExitNode => is_tor_exit_node = Some(read_bool(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -231,11 +439,19 @@ pub struct Country<'a> {
     pub country: Option<models::Country<'a>>,
     pub registered_country: Option<models::Country<'a>>,
     pub represented_country: Option<models::RepresentedCountry<'a>>,
-    pub traits: Option<models::Traits>,
+    pub traits: Option<models::Traits<'a>>,
+}
+
+impl<'a> Country<'a> {
+    /// The country name in the first of `languages` available, usually
+    /// `reader.languages()`.
+    pub fn country_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.country.as_ref()?.names.as_ref()?.best(languages)
+    }
 }
 
 impl<'a> Decoder<'a> for Country<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut continent = None;
         let mut country = None;
         let mut registered_country = None;
@@ -243,21 +459,21 @@ impl<'a> Decoder<'a> for Country<'a> {
         let mut traits = None;
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "continent" => {
+            match read_key(buf, offset)? {
+                Key::Continent => {
                     continent = Some(models::Continent::decode(buf, offset)?);
                 }
-                "country" => {
+                Key::Country => {
                     country = Some(models::Country::decode(buf, offset)?);
                 }
-                "registered_country" => {
+                Key::RegisteredCountry => {
                     registered_country = Some(models::Country::decode(buf, offset)?);
                 }
-                "represented_country" => {
+                Key::RepresentedCountry => {
                     represented_country = Some(models::RepresentedCountry::decode(buf, offset)?)
                 }
-                "traits" => traits = Some(models::Traits::decode(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::Traits => traits = Some(models::Traits::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -281,65 +497,84 @@ pub struct City<'a> {
     pub registered_country: Option<models::Country<'a>>,
     pub represented_country: Option<models::RepresentedCountry<'a>>,
     pub subdivisions: Option<Vec<models::Subdivision<'a>>>,
-    pub traits: Option<models::Traits>,
+    pub traits: Option<models::Traits<'a>>,
+}
+
+impl<'a> City<'a> {
+    /// The city name in the first of `languages` available, usually
+    /// `reader.languages()`.
+    pub fn city_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.city.as_ref()?.names.as_ref()?.best(languages)
+    }
+
+    /// The name of the most specific subdivision.
+    pub fn subdivision_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.subdivisions
+            .as_ref()?
+            .last()?
+            .names
+            .as_ref()?
+            .best(languages)
+    }
+
+    pub fn country_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.country.as_ref()?.names.as_ref()?.best(languages)
+    }
+
+    /// "City, Subdivision, Country", the parts without a name in any of
+    /// `languages` are left out.
+    pub fn display_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<String> {
+        display_name([
+            self.city_name(languages),
+            self.subdivision_name(languages),
+            self.country_name(languages),
+        ])
+    }
 }
 
 impl<'a> Decoder<'a> for City<'a> {
     #[inline]
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut city = City::default();
+        city.decode_into(buf, offset, size)?;
+
+        Ok(city)
+    }
+
+    fn decode_into(&mut self, buf: Data<'a>, offset: &mut usize, size: usize) -> Result<(), Error> {
+        let mut subdivisions = self.subdivisions.take();
+        *self = City::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "city" => city.city = Some(models::City::decode(buf, offset)?),
-                "continent" => city.continent = Some(models::Continent::decode(buf, offset)?),
-                "country" => city.country = Some(models::Country::decode(buf, offset)?),
-                "location" => city.location = Some(models::Location::decode(buf, offset)?),
-                "postal" => city.postal = Some(models::Postal::decode(buf, offset)?),
-                "registered_country" => {
-                    city.registered_country = Some(models::Country::decode(buf, offset)?)
+            match read_key(buf, offset)? {
+                Key::City => self.city = Some(models::City::decode(buf, offset)?),
+                Key::Continent => self.continent = Some(models::Continent::decode(buf, offset)?),
+                Key::Country => self.country = Some(models::Country::decode(buf, offset)?),
+                Key::Location => self.location = Some(models::Location::decode(buf, offset)?),
+                Key::Postal => self.postal = Some(models::Postal::decode(buf, offset)?),
+                Key::RegisteredCountry => {
+                    self.registered_country = Some(models::Country::decode(buf, offset)?)
                 }
-                "represented_country" => {
-                    city.represented_country =
+                Key::RepresentedCountry => {
+                    self.represented_country =
                         Some(models::RepresentedCountry::decode(buf, offset)?)
                 }
-                "subdivisions" => {
-                    let (data_type, size) = read_control(buf, offset)?;
-                    city.subdivisions = match data_type {
-                        DATA_TYPE_SLICE => {
-                            let mut array = Vec::with_capacity(size);
-                            for _ in 0..size {
-                                let item = models::Subdivision::decode(buf, offset)?;
-                                array.push(item);
-                            }
-
-                            Some(array)
-                        }
-                        DATA_TYPE_POINTER => {
-                            let offset = &mut read_pointer(buf, offset, size)?;
-                            let (data_type, size) = read_control(buf, offset)?;
-                            match data_type {
-                                DATA_TYPE_SLICE => {
-                                    let mut array = Vec::with_capacity(size);
-                                    for _ in 0..size {
-                                        let item = models::Subdivision::decode(buf, offset)?;
-                                        array.push(item);
-                                    }
-
-                                    Some(array)
-                                }
-                                _ => return Err(Error::InvalidDataType(data_type)),
-                            }
-                        }
-                        _ => return Err(Error::InvalidDataType(data_type)),
-                    };
+                Key::Subdivisions => {
+                    self.subdivisions = subdivisions.take();
+                    self.subdivisions.decode_in_place(buf, offset)?;
                 }
-                "traits" => city.traits = Some(models::Traits::decode(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::Traits => self.traits = Some(models::Traits::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
-        Ok(city)
+        // a record without subdivisions keeps the buffer, empty
+        if let Some(mut spare) = subdivisions {
+            spare.clear();
+            self.subdivisions = Some(spare);
+        }
+
+        Ok(())
     }
 }
 
@@ -357,71 +592,91 @@ pub struct Enterprise<'a> {
     pub traits: Option<models::EnterpriseTraits<'a>>,
 }
 
+impl<'a> Enterprise<'a> {
+    /// The city name in the first of `languages` available, usually
+    /// `reader.languages()`.
+    pub fn city_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.city.as_ref()?.names.as_ref()?.best(languages)
+    }
+
+    /// The name of the most specific subdivision.
+    pub fn subdivision_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.subdivisions
+            .as_ref()?
+            .last()?
+            .names
+            .as_ref()?
+            .best(languages)
+    }
+
+    pub fn country_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<&'a str> {
+        self.country.as_ref()?.names.as_ref()?.best(languages)
+    }
+
+    /// "City, Subdivision, Country", the parts without a name in any of
+    /// `languages` are left out.
+    pub fn display_name<L: AsRef<str>>(&self, languages: &[L]) -> Option<String> {
+        display_name([
+            self.city_name(languages),
+            self.subdivision_name(languages),
+            self.country_name(languages),
+        ])
+    }
+}
+
 impl<'a> Decoder<'a> for Enterprise<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut enterprise = Enterprise::default();
+        enterprise.decode_into(buf, offset, size)?;
+
+        Ok(enterprise)
+    }
+
+    fn decode_into(&mut self, buf: Data<'a>, offset: &mut usize, size: usize) -> Result<(), Error> {
+        let mut subdivisions = self.subdivisions.take();
+        *self = Enterprise::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "city" => enterprise.city = Some(models::EnterpriseCity::decode(buf, offset)?),
-                "continent" => enterprise.continent = Some(models::Continent::decode(buf, offset)?),
-                "country" => {
-                    enterprise.country = Some(models::EnterpriseCountry::decode(buf, offset)?)
-                }
-                "location" => enterprise.location = Some(models::Location::decode(buf, offset)?),
-                "postal" => {
-                    enterprise.postal = Some(models::EnterprisePostal::decode(buf, offset)?)
+            match read_key(buf, offset)? {
+                Key::City => self.city = Some(models::EnterpriseCity::decode(buf, offset)?),
+                Key::Continent => self.continent = Some(models::Continent::decode(buf, offset)?),
+                Key::Country => {
+                    self.country = Some(models::EnterpriseCountry::decode(buf, offset)?)
                 }
-                "registered_country" => {
-                    enterprise.registered_country =
-                        Some(models::EnterpriseCountry::decode(buf, offset)?)
+                Key::Location => self.location = Some(models::Location::decode(buf, offset)?),
+                Key::Postal => self.postal = Some(models::EnterprisePostal::decode(buf, offset)?),
+                Key::RegisteredCountry => {
+                    self.registered_country = Some(models::EnterpriseCountry::decode(buf, offset)?)
                 }
-                "represented_country" => {
-                    enterprise.represented_country =
+                Key::RepresentedCountry => {
+                    self.represented_country =
                         Some(models::EnterpriseRepresentedCountry::decode(buf, offset)?)
                 }
-                "subdivisions" => {
-                    let (data_type, size) = read_control(buf, offset)?;
-
-                    enterprise.subdivisions = Some(match data_type {
-                        DATA_TYPE_SLICE => {
-                            let mut array = Vec::with_capacity(size);
-
-                            for _ in 0..size {
-                                let item = models::EnterpriseSubdivision::decode(buf, offset)?;
-                                array.push(item);
-                            }
-
-                            array
-                        }
-                        DATA_TYPE_POINTER => {
-                            let offset = &mut read_pointer(buf, offset, size)?;
-                            let (data_type, size) = read_control(buf, offset)?;
-                            match data_type {
-                                DATA_TYPE_SLICE => {
-                                    let mut array = Vec::with_capacity(size);
-                                    for _ in 0..size {
-                                        let item =
-                                            models::EnterpriseSubdivision::decode(buf, offset)?;
-                                        array.push(item);
-                                    }
-
-                                    array
-                                }
-                                _ => return Err(Error::InvalidDataType(data_type)),
-                            }
-                        }
-                        _ => return Err(Error::InvalidDataType(data_type)),
-                    });
+                Key::Subdivisions => {
+                    self.subdivisions = subdivisions.take();
+                    self.subdivisions.decode_in_place(buf, offset)?;
                 }
-                "traits" => {
-                    enterprise.traits = Some(models::EnterpriseTraits::decode(buf, offset)?)
-                }
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::Traits => self.traits = Some(models::EnterpriseTraits::decode(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
-        Ok(enterprise)
+        // a record without subdivisions keeps the buffer, empty
+        if let Some(mut spare) = subdivisions {
+            spare.clear();
+            self.subdivisions = Some(spare);
+        }
+
+        Ok(())
+    }
+}
+
+fn display_name(parts: [Option<&str>; 3]) -> Option<String> {
+    let parts = parts.into_iter().flatten().collect::<Vec<_>>();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(", "))
     }
 }
 
@@ -431,14 +686,22 @@ pub struct ConnectionType<'a> {
     pub connection_type: Option<&'a str>,
 }
 
+impl<'a> ConnectionType<'a> {
+    pub fn connection_kind(&self) -> Option<models::ConnectionKind<'a>> {
+        self.connection_type.map(models::ConnectionKind::from)
+    }
+}
+
 impl<'a> Decoder<'a> for ConnectionType<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut connection_type = ConnectionType::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "connection_type" => connection_type.connection_type = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::ConnectionType => {
+                    connection_type.connection_type = Some(read_str(buf, offset)?)
+                }
+                key => return Err(key.unknown()),
             }
         }
 
@@ -453,13 +716,13 @@ pub struct Domain<'a> {
 }
 
 impl<'a> Decoder<'a> for Domain<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut domain = Domain::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "domain" => domain.domain = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+            match read_key(buf, offset)? {
+                Key::Domain => domain.domain = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -479,22 +742,22 @@ pub struct Isp<'a> {
 }
 
 impl<'a> Decoder<'a> for Isp<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut isp = Isp::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "autonomous_system_number" => {
-                    isp.autonomous_system_number = Some(read_usize(buf, offset)? as u32)
+            match read_key(buf, offset)? {
+                Key::AutonomousSystemNumber => {
+                    isp.autonomous_system_number = Some(read_u32(buf, offset)?)
                 }
-                "autonomous_system_organization" => {
+                Key::AutonomousSystemOrganization => {
                     isp.autonomous_system_organization = Some(read_str(buf, offset)?)
                 }
-                "isp" => isp.isp = Some(read_str(buf, offset)?),
-                "mobile_country_code" => isp.mobile_country_code = Some(read_str(buf, offset)?),
-                "mobile_network_code" => isp.mobile_network_code = Some(read_str(buf, offset)?),
-                "organization" => isp.organization = Some(read_str(buf, offset)?),
-                field => return Err(Error::UnknownField(field.to_string())),
+                Key::Isp => isp.isp = Some(read_str(buf, offset)?),
+                Key::MobileCountryCode => isp.mobile_country_code = Some(read_str(buf, offset)?),
+                Key::MobileNetworkCode => isp.mobile_network_code = Some(read_str(buf, offset)?),
+                Key::Organization => isp.organization = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
             }
         }
 
@@ -510,21 +773,235 @@ pub struct Asn<'a> {
 }
 
 impl<'a> Decoder<'a> for Asn<'a> {
-    fn decode_with_size(buf: &'a [u8], offset: &mut usize, size: usize) -> Result<Self, Error> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
         let mut asn = Asn::default();
 
         for _ in 0..size {
-            match read_str(buf, offset)? {
-                "autonomous_system_number" => {
-                    asn.autonomous_system_number = Some(read_usize(buf, offset)? as u32)
+            match read_key(buf, offset)? {
+                Key::AutonomousSystemNumber => {
+                    asn.autonomous_system_number = Some(read_u32(buf, offset)?)
                 }
-                "autonomous_system_organization" => {
+                Key::AutonomousSystemOrganization => {
                     asn.autonomous_system_organization = Some(read_str(buf, offset)?)
                 }
-                field => return Err(Error::UnknownField(field.to_string())),
+                key => return Err(key.unknown()),
             }
         }
 
         Ok(asn)
     }
 }
+
+/// GeoIP Anonymous Plus record
+#[derive(Clone, Debug, Default)]
+pub struct AnonymousPlus<'a> {
+    /// How confident MaxMind is that the network is an anonymizer, from 1
+    /// to 99.
+    pub anonymizer_confidence: Option<u16>,
+    pub is_anonymous: Option<bool>,
+    pub is_anonymous_vpn: Option<bool>,
+    pub is_hosting_provider: Option<bool>,
+    pub is_public_proxy: Option<bool>,
+    pub is_residential_proxy: Option<bool>,
+    pub is_tor_exit_node: Option<bool>,
+    /// The last day the network was seen in anonymizer traffic, as
+    /// `YYYY-MM-DD`.
+    pub network_last_seen: Option<&'a str>,
+    pub provider_name: Option<&'a str>,
+}
+
+impl<'a> Decoder<'a> for AnonymousPlus<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut anonymous_plus = AnonymousPlus::default();
+
+        for _ in 0..size {
+            match read_key(buf, offset)? {
+                Key::AnonymizerConfidence => {
+                    anonymous_plus.anonymizer_confidence = Some(read_u16(buf, offset)?)
+                }
+                Key::IsAnonymous => anonymous_plus.is_anonymous = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousVpn => {
+                    anonymous_plus.is_anonymous_vpn = Some(read_bool(buf, offset)?)
+                }
+                Key::IsHostingProvider => {
+                    anonymous_plus.is_hosting_provider = Some(read_bool(buf, offset)?)
+                }
+                Key::IsPublicProxy => {
+                    anonymous_plus.is_public_proxy = Some(read_bool(buf, offset)?)
+                }
+                Key::IsResidentialProxy => {
+                    anonymous_plus.is_residential_proxy = Some(read_bool(buf, offset)?)
+                }
+                Key::IsTorExitNode => {
+                    anonymous_plus.is_tor_exit_node = Some(read_bool(buf, offset)?)
+                }
+                Key::NetworkLastSeen => {
+                    anonymous_plus.network_last_seen = Some(read_str(buf, offset)?)
+                }
+                Key::ProviderName => anonymous_plus.provider_name = Some(read_str(buf, offset)?),
+                key => return Err(key.unknown()),
+            }
+        }
+
+        Ok(anonymous_plus)
+    }
+}
+
+/// GeoIP2 IP Risk record
+#[derive(Clone, Debug, Default)]
+pub struct IpRisk {
+    /// Risk of the IP address, from 0.01 to 99.
+    pub ip_risk: Option<f64>,
+    pub is_anonymous: Option<bool>,
+    pub is_anonymous_vpn: Option<bool>,
+    pub is_hosting_provider: Option<bool>,
+    pub is_public_proxy: Option<bool>,
+    pub is_residential_proxy: Option<bool>,
+    pub is_tor_exit_node: Option<bool>,
+}
+
+impl<'a> Decoder<'a> for IpRisk {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut ip_risk = IpRisk::default();
+
+        for _ in 0..size {
+            match read_key(buf, offset)? {
+                Key::IpRisk => ip_risk.ip_risk = Some(read_f64(buf, offset)?),
+                Key::IsAnonymous => ip_risk.is_anonymous = Some(read_bool(buf, offset)?),
+                Key::IsAnonymousVpn => ip_risk.is_anonymous_vpn = Some(read_bool(buf, offset)?),
+                Key::IsHostingProvider => {
+                    ip_risk.is_hosting_provider = Some(read_bool(buf, offset)?)
+                }
+                Key::IsPublicProxy => ip_risk.is_public_proxy = Some(read_bool(buf, offset)?),
+                Key::IsResidentialProxy => {
+                    ip_risk.is_residential_proxy = Some(read_bool(buf, offset)?)
+                }
+                Key::IsTorExitNode => ip_risk.is_tor_exit_node = Some(read_bool(buf, offset)?),
+                key => return Err(key.unknown()),
+            }
+        }
+
+        Ok(ip_risk)
+    }
+}
+
+/// GeoIP2 Static IP Score record
+#[derive(Clone, Debug, Default)]
+pub struct StaticIpScore {
+    /// How static the IP address is, from 0 to 99.99.
+    pub score: Option<f64>,
+}
+
+impl<'a> Decoder<'a> for StaticIpScore {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut static_ip_score = StaticIpScore::default();
+
+        for _ in 0..size {
+            match read_key(buf, offset)? {
+                Key::Score => static_ip_score.score = Some(read_f64(buf, offset)?),
+                key => return Err(key.unknown()),
+            }
+        }
+
+        Ok(static_ip_score)
+    }
+}
+
+/// GeoIP2 User Count record, the number of users seen in the past 24 hours
+/// in the surrounding networks.
+#[derive(Clone, Debug, Default)]
+pub struct UserCount {
+    pub ipv4_24: Option<u32>,
+    pub ipv4_32: Option<u32>,
+    pub ipv6_32: Option<u32>,
+    pub ipv6_48: Option<u32>,
+    pub ipv6_64: Option<u32>,
+}
+
+impl<'a> Decoder<'a> for UserCount {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut user_count = UserCount::default();
+
+        for _ in 0..size {
+            match read_key(buf, offset)? {
+                Key::Ipv4_24 => user_count.ipv4_24 = Some(read_u32(buf, offset)?),
+                Key::Ipv4_32 => user_count.ipv4_32 = Some(read_u32(buf, offset)?),
+                Key::Ipv6_32 => user_count.ipv6_32 = Some(read_u32(buf, offset)?),
+                Key::Ipv6_48 => user_count.ipv6_48 = Some(read_u32(buf, offset)?),
+                Key::Ipv6_64 => user_count.ipv6_64 = Some(read_u32(buf, offset)?),
+                key => return Err(key.unknown()),
+            }
+        }
+
+        Ok(user_count)
+    }
+}
+
+/// GeoIP2 DensityIncome record
+#[derive(Clone, Debug, Default)]
+pub struct DensityIncome {
+    /// Average income in US dollars.
+    pub average_income: Option<u32>,
+    /// Population per square kilometer.
+    pub population_density: Option<u32>,
+}
+
+impl<'a> Decoder<'a> for DensityIncome {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut density_income = DensityIncome::default();
+
+        for _ in 0..size {
+            match read_key(buf, offset)? {
+                Key::AverageIncome => density_income.average_income = Some(read_u32(buf, offset)?),
+                Key::PopulationDensity => {
+                    density_income.population_density = Some(read_u32(buf, offset)?)
+                }
+                key => return Err(key.unknown()),
+            }
+        }
+
+        Ok(density_income)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_record, Reader, DATA_SECTION_SEPARATOR_SIZE, METADATA_START_MARKER};
+    use crate::Error;
+
+    #[test]
+    fn record_sizes() {
+        let tree = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+
+        let records = |node, index| unsafe {
+            (
+                read_record::<24>(&tree, node, index),
+                read_record::<28>(&tree[..7], node, index),
+                read_record::<32>(&tree[..8], node, index),
+            )
+        };
+        assert_eq!(records(0, 0), (0x010203, 0x0010203, 0x01020304));
+        assert_eq!(records(0, 1), (0x040506, 0x4050607, 0x05060708));
+
+        let tree = [0xff, 0xff, 0xff, 0xf0, 0x00, 0x00, 0x01];
+        assert_eq!(unsafe { read_record::<28>(&tree, 0, 0) }, 0xfffffff);
+        assert_eq!(unsafe { read_record::<28>(&tree, 0, 1) }, 0x1);
+    }
+
+    #[test]
+    fn search_tree_size_overflow() {
+        // 2^61 nodes of 8 bytes wrap the size of the tree to 0
+        let mut buf = vec![0; DATA_SECTION_SEPARATOR_SIZE];
+        buf.extend_from_slice(METADATA_START_MARKER);
+        buf.extend_from_slice(&[0xe2, 0x4a]);
+        buf.extend_from_slice(b"node_count");
+        buf.extend_from_slice(&[0x08, 0x02, 0x20, 0, 0, 0, 0, 0, 0, 0, 0x4b]);
+        buf.extend_from_slice(b"record_size");
+        buf.extend_from_slice(&[0xa1, 0x20]);
+
+        assert!(matches!(
+            Reader::from_bytes(buf),
+            Err(Error::InvalidSearchTreeSize)
+        ));
+    }
+}
@@ -0,0 +1,572 @@
+//! Schema of a whole database inferred from its records, to find out the
+//! shape of a database nobody documented.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::decode::{
+    type_name, DATA_TYPE_BOOL, DATA_TYPE_BYTES, DATA_TYPE_FLOAT32, DATA_TYPE_FLOAT64,
+    DATA_TYPE_INT32, DATA_TYPE_MAP, DATA_TYPE_SLICE, DATA_TYPE_STRING, DATA_TYPE_UINT128,
+    DATA_TYPE_UINT16, DATA_TYPE_UINT32, DATA_TYPE_UINT64,
+};
+use crate::json::write_json_str;
+use crate::visit::{walk, Visitor, Walk};
+use crate::{Error, Reader};
+
+// Distinct values kept as examples of a field.
+const MAX_EXAMPLES: usize = 3;
+
+/// The merged schema of every record of a database, see
+/// [`Reader::infer_schema`].
+#[derive(Clone, Debug)]
+pub struct Schema {
+    pub database_type: String,
+    /// Networks with a record. A subtree reached through several paths,
+    /// like the IPv4 subtree of an IPv6 database, counts once.
+    pub networks: u64,
+    /// Distinct records, networks sharing a record count it once.
+    pub records: usize,
+    /// The schema of the records themselves.
+    pub root: Field,
+}
+
+/// A value of the records, a map entry or the elements of an array.
+///
+/// Counts are weighted by the number of networks sharing the record.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Field {
+    /// Number of times the value was seen, every element of an array
+    /// counts.
+    pub count: u64,
+    /// Number of networks whose record has the value at least once.
+    pub networks: u64,
+    /// Number of times each data type was seen, by its MaxMind DB spec
+    /// name, e.g. `uint32`.
+    pub types: BTreeMap<&'static str, u64>,
+    /// Entries of the maps seen.
+    pub fields: BTreeMap<String, Field>,
+    /// Elements of the arrays seen, merged.
+    pub items: Option<Box<Field>>,
+    /// The first distinct scalars seen, as JSON.
+    pub examples: Vec<String>,
+}
+
+impl Field {
+    // entries seen in every map, JSON Schema `required`
+    fn required(&self) -> impl Iterator<Item = &str> {
+        let maps = self.types.get(type_name(DATA_TYPE_MAP)).copied();
+        self.fields
+            .iter()
+            .filter(move |(_, field)| Some(field.count) == maps)
+            .map(|(key, _)| key.as_str())
+    }
+
+    fn json_types(&self) -> Vec<&'static str> {
+        let mut types = Vec::new();
+        for &name in self.types.keys() {
+            let json_type = match name {
+                "map" => "object",
+                "array" | "bytes" => "array",
+                "utf8_string" => "string",
+                "double" | "float" => "number",
+                "boolean" => "boolean",
+                _ => "integer",
+            };
+            if !types.contains(&json_type) {
+                types.push(json_type);
+            }
+        }
+        types
+    }
+}
+
+impl<S: AsRef<[u8]>> Reader<S> {
+    /// Walk the record of every network and merge them into a single
+    /// schema. Each distinct record is walked once, however many networks
+    /// or pointers share it.
+    pub fn infer_schema(&self) -> Result<Schema, Error> {
+        // data offsets of the records, with the number of networks
+        // pointing to each
+        let mut records = BTreeMap::new();
+        let mut visited = vec![false; self.node_count];
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            match visited.get_mut(node) {
+                Some(seen) if !*seen => *seen = true,
+                _ => continue,
+            }
+
+            for index in 0..2 {
+                let record = self.read_node(node, index);
+                if record < self.node_count {
+                    stack.push(record);
+                } else if record > self.node_count {
+                    *records
+                        .entry(self.resolve_data_pointer(record)?)
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut inference = Inference {
+            nodes: vec![Node::default()],
+            stack: Vec::new(),
+            next: 0,
+            record: 0,
+            weight: 0,
+        };
+        let buf = self.data();
+        for (record, (&offset, &weight)) in records.iter().enumerate() {
+            inference.record = record + 1;
+            inference.weight = weight;
+            walk(buf, &mut offset.clone(), &mut inference)?;
+        }
+
+        Ok(Schema {
+            database_type: self.metadata()?.database_type.to_string(),
+            networks: records.values().sum(),
+            records: records.len(),
+            root: inference.field(0),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    count: u64,
+    networks: u64,
+    // the last record the node was seen in, to count networks once per record
+    record: usize,
+    types: BTreeMap<&'static str, u64>,
+    fields: BTreeMap<String, usize>,
+    items: Option<usize>,
+    examples: Vec<String>,
+}
+
+enum Frame {
+    Map(usize),
+    // the node of the elements
+    Array(usize),
+}
+
+/// Merges the records it visits into a tree of nodes, the root first.
+struct Inference {
+    nodes: Vec<Node>,
+    stack: Vec<Frame>,
+    // the node of the value of the last key
+    next: usize,
+    record: usize,
+    weight: u64,
+}
+
+impl Inference {
+    // the node of the value visited
+    fn target(&self) -> usize {
+        match self.stack.last() {
+            Some(Frame::Map(_)) => self.next,
+            Some(Frame::Array(items)) => *items,
+            None => 0,
+        }
+    }
+
+    fn add(&mut self, data_type: u8, example: impl FnOnce() -> String) -> usize {
+        let index = self.target();
+        let node = &mut self.nodes[index];
+        node.count += self.weight;
+        if node.record != self.record {
+            node.record = self.record;
+            node.networks += self.weight;
+        }
+        *node.types.entry(type_name(data_type)).or_insert(0) += self.weight;
+
+        if node.examples.len() < MAX_EXAMPLES {
+            let example = example();
+            if !example.is_empty() && !node.examples.contains(&example) {
+                node.examples.push(example);
+            }
+        }
+        index
+    }
+
+    fn field(&self, index: usize) -> Field {
+        let node = &self.nodes[index];
+        Field {
+            count: node.count,
+            networks: node.networks,
+            types: node.types.clone(),
+            fields: node
+                .fields
+                .iter()
+                .map(|(key, &child)| (key.clone(), self.field(child)))
+                .collect(),
+            items: node.items.map(|items| Box::new(self.field(items))),
+            examples: node.examples.clone(),
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Inference {
+    fn visit_map_start(&mut self, _len: usize) -> Result<Walk, Error> {
+        let index = self.add(DATA_TYPE_MAP, String::new);
+        self.stack.push(Frame::Map(index));
+        Ok(Walk::Enter)
+    }
+
+    fn visit_key(&mut self, key: &'a str) -> Result<Walk, Error> {
+        let map = match self.stack.last() {
+            Some(&Frame::Map(map)) => map,
+            _ => unreachable!("keys are only visited in maps"),
+        };
+
+        self.next = match self.nodes[map].fields.get(key) {
+            Some(&child) => child,
+            None => {
+                let child = self.nodes.len();
+                self.nodes.push(Node::default());
+                self.nodes[map].fields.insert(key.to_string(), child);
+                child
+            }
+        };
+        Ok(Walk::Enter)
+    }
+
+    fn visit_map_end(&mut self) -> Result<(), Error> {
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn visit_array_start(&mut self, _len: usize) -> Result<Walk, Error> {
+        // arrays have no examples, their elements do
+        let index = self.add(DATA_TYPE_SLICE, String::new);
+        let items = match self.nodes[index].items {
+            Some(items) => items,
+            None => {
+                let items = self.nodes.len();
+                self.nodes.push(Node::default());
+                self.nodes[index].items = Some(items);
+                items
+            }
+        };
+        self.stack.push(Frame::Array(items));
+        Ok(Walk::Enter)
+    }
+
+    fn visit_array_end(&mut self) -> Result<(), Error> {
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &'a str) -> Result<(), Error> {
+        self.add(DATA_TYPE_STRING, || {
+            let mut out = Vec::new();
+            write_json_str(&mut out, value).unwrap();
+            String::from_utf8(out).unwrap()
+        });
+        Ok(())
+    }
+
+    fn visit_bytes(&mut self, value: &'a [u8]) -> Result<(), Error> {
+        // like JSON output, an array of numbers
+        self.add(DATA_TYPE_BYTES, || {
+            let bytes = value.iter().map(u8::to_string).collect::<Vec<_>>();
+            format!("[{}]", bytes.join(","))
+        });
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+        self.add(DATA_TYPE_BOOL, || value.to_string());
+        Ok(())
+    }
+
+    fn visit_u16(&mut self, value: u16) -> Result<(), Error> {
+        self.add(DATA_TYPE_UINT16, || value.to_string());
+        Ok(())
+    }
+
+    fn visit_u32(&mut self, value: u32) -> Result<(), Error> {
+        self.add(DATA_TYPE_UINT32, || value.to_string());
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        self.add(DATA_TYPE_UINT64, || value.to_string());
+        Ok(())
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        self.add(DATA_TYPE_UINT128, || value.to_string());
+        Ok(())
+    }
+
+    fn visit_i32(&mut self, value: i32) -> Result<(), Error> {
+        self.add(DATA_TYPE_INT32, || value.to_string());
+        Ok(())
+    }
+
+    fn visit_f32(&mut self, value: f32) -> Result<(), Error> {
+        self.add(DATA_TYPE_FLOAT32, || json_float(value, value.is_finite()));
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        self.add(DATA_TYPE_FLOAT64, || json_float(value, value.is_finite()));
+        Ok(())
+    }
+}
+
+fn json_float(value: impl Display, finite: bool) -> String {
+    if finite {
+        value.to_string()
+    } else {
+        // JSON has no infinities
+        "null".to_string()
+    }
+}
+
+impl Schema {
+    /// Write the schema as a JSON Schema (draft 2020-12). Data types are
+    /// kept in `format`, e.g. `uint32`, and how often a field is present in
+    /// its `description`.
+    pub fn write_json_schema<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "{{")?;
+        writeln!(
+            out,
+            "  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\","
+        )?;
+        out.write_all(b"  \"title\": ")?;
+        write_json_str(out, &self.database_type)?;
+        writeln!(out, ",")?;
+        self.write_field(out, &self.root, 1)?;
+        writeln!(out, "}}")
+    }
+
+    fn write_field<W: Write>(
+        &self,
+        out: &mut W,
+        field: &Field,
+        depth: usize,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(depth);
+
+        let types = field.json_types();
+        match types.as_slice() {
+            [] => {}
+            [json_type] => writeln!(out, "{indent}\"type\": \"{json_type}\",")?,
+            types => writeln!(out, "{indent}\"type\": [\"{}\"],", types.join("\", \""))?,
+        }
+        if !field.types.is_empty() {
+            let formats = field.types.keys().copied().collect::<Vec<_>>();
+            writeln!(out, "{indent}\"format\": \"{}\",", formats.join("|"))?;
+        }
+
+        if !field.fields.is_empty() {
+            writeln!(out, "{indent}\"properties\": {{")?;
+            for (i, (key, child)) in field.fields.iter().enumerate() {
+                out.write_all(indent.as_bytes())?;
+                out.write_all(b"  ")?;
+                write_json_str(out, key)?;
+                out.write_all(b": {\n")?;
+                self.write_field(out, child, depth + 2)?;
+                let sep = if i + 1 < field.fields.len() { "," } else { "" };
+                writeln!(out, "{indent}  }}{sep}")?;
+            }
+            writeln!(out, "{indent}}},")?;
+
+            let required = field.required().collect::<Vec<_>>();
+            if !required.is_empty() {
+                write!(out, "{indent}\"required\": [")?;
+                for (i, key) in required.into_iter().enumerate() {
+                    if i > 0 {
+                        out.write_all(b", ")?;
+                    }
+                    write_json_str(out, key)?;
+                }
+                out.write_all(b"],\n")?;
+            }
+        }
+
+        if let Some(items) = &field.items {
+            writeln!(out, "{indent}\"items\": {{")?;
+            self.write_field(out, items, depth + 1)?;
+            writeln!(out, "{indent}}},")?;
+        }
+
+        if !field.examples.is_empty() {
+            writeln!(
+                out,
+                "{indent}\"examples\": [{}],",
+                field.examples.join(", ")
+            )?;
+        }
+
+        writeln!(
+            out,
+            "{indent}\"description\": \"in {:.1}% of records\"",
+            self.percent(field)
+        )
+    }
+
+    // share of the networks whose record has the field
+    fn percent(&self, field: &Field) -> f64 {
+        if self.networks == 0 {
+            return 0.0;
+        }
+        field.networks as f64 * 100.0 / self.networks as f64
+    }
+}
+
+impl Display for Schema {
+    /// One line per field: its path, data types, how often it's present
+    /// and examples. Elements of arrays are under `[]`, e.g.
+    /// `subdivisions[].iso_code`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rows = Vec::new();
+        rows_of(&mut rows, String::new(), &self.root);
+        let rows = rows
+            .into_iter()
+            .map(|(path, field)| {
+                let types = field.types.keys().copied().collect::<Vec<_>>();
+                (path, types.join("|"), field)
+            })
+            .collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|(path, ..)| path.len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let types_width = rows
+            .iter()
+            .map(|(_, types, _)| types.len())
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            f,
+            "{}, {} networks, {} records",
+            self.database_type, self.networks, self.records
+        )?;
+        for (path, types, field) in rows {
+            let path = if path.is_empty() { "." } else { &path };
+            write!(
+                f,
+                "{path:<width$}  {types:<types_width$}  {:>5.1}%",
+                self.percent(field)
+            )?;
+            if !field.examples.is_empty() {
+                write!(f, "  {}", field.examples.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn rows_of<'s>(rows: &mut Vec<(String, &'s Field)>, path: String, field: &'s Field) {
+    rows.push((path.clone(), field));
+    for (key, child) in &field.fields {
+        let child_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        rows_of(rows, child_path, child);
+    }
+    if let Some(items) = &field.items {
+        rows_of(rows, format!("{path}[]"), items);
+    }
+}
+
+// the data types a field can have
+const DATA_TYPES: [u8; 12] = [
+    DATA_TYPE_STRING,
+    DATA_TYPE_FLOAT64,
+    DATA_TYPE_BYTES,
+    DATA_TYPE_UINT16,
+    DATA_TYPE_UINT32,
+    DATA_TYPE_MAP,
+    DATA_TYPE_INT32,
+    DATA_TYPE_UINT64,
+    DATA_TYPE_UINT128,
+    DATA_TYPE_SLICE,
+    DATA_TYPE_BOOL,
+    DATA_TYPE_FLOAT32,
+];
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    /// Parse a schema in the text format it's displayed in, e.g. one
+    /// written by hand. Only the path and types of a line are read, and
+    /// the maps and arrays a path goes through don't need a line of their
+    /// own. Blank lines and lines starting with `#` are skipped, the
+    /// `<database type>, <n> networks, <n> records` header is optional.
+    /// Counts are left at 0.
+    fn from_str(s: &str) -> Result<Schema, Error> {
+        let mut schema = Schema {
+            database_type: String::new(),
+            networks: 0,
+            records: 0,
+            root: Field::default(),
+        };
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                |reason: String| Error::InvalidSchema(format!("line {}, {reason}", i + 1));
+
+            if let Some(header) = parse_header(line) {
+                (schema.database_type, schema.networks, schema.records) = header;
+                continue;
+            }
+
+            let mut columns = line.split_whitespace();
+            let path = columns.next().unwrap_or_default();
+            let types = columns
+                .next()
+                .ok_or_else(|| invalid(format!("no type for {path}")))?;
+
+            let mut field = &mut schema.root;
+            if path != "." {
+                for (j, segment) in path.split('.').enumerate() {
+                    let key = segment.trim_end_matches("[]");
+                    if !key.is_empty() {
+                        field.types.entry(type_name(DATA_TYPE_MAP)).or_insert(0);
+                        field = field.fields.entry(key.to_string()).or_default();
+                    } else if j > 0 || segment.is_empty() {
+                        return Err(invalid(format!("invalid path {path}")));
+                    }
+
+                    for _ in 0..(segment.len() - key.len()) / 2 {
+                        field.types.entry(type_name(DATA_TYPE_SLICE)).or_insert(0);
+                        field = field.items.get_or_insert_with(Default::default);
+                    }
+                }
+            }
+
+            for name in types.split('|') {
+                let data_type = DATA_TYPES
+                    .into_iter()
+                    .find(|&data_type| type_name(data_type) == name)
+                    .ok_or_else(|| invalid(format!("unknown type {name}")))?;
+                field.types.entry(type_name(data_type)).or_insert(0);
+            }
+        }
+
+        Ok(schema)
+    }
+}
+
+// `<database type>, <n> networks, <n> records`
+fn parse_header(line: &str) -> Option<(String, u64, usize)> {
+    let mut parts = line.rsplitn(3, ", ");
+    let records = parts.next()?.strip_suffix(" records")?.parse().ok()?;
+    let networks = parts.next()?.strip_suffix(" networks")?.parse().ok()?;
+    Some((parts.next()?.to_string(), networks, records))
+}
@@ -0,0 +1,281 @@
+//! Records mapped to OpenTelemetry attributes and Elastic Common Schema
+//! fields.
+//!
+//! OpenTelemetry: <https://opentelemetry.io/docs/specs/semconv/attributes-registry/geo/>
+//! and the `network.carrier.*` attributes of the network registry.
+//! ECS: <https://www.elastic.co/guide/en/ecs/current/ecs-geo.html>, and
+//! `as.*`, both under `source`.
+
+use std::borrow::Cow;
+
+use crate::models::Names;
+use crate::{Asn, City, Enterprise, Isp};
+
+/// The value of an attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue<'a> {
+    String(Cow<'a, str>),
+    Int(i64),
+    Double(f64),
+    /// An ECS `geo_point`, only in ECS fields.
+    GeoPoint {
+        lat: f64,
+        lon: f64,
+    },
+}
+
+/// Iterator of `(name, value)` attribute pairs, the ones without a value
+/// in the record are left out.
+#[derive(Clone, Debug)]
+pub struct Attributes<'a>(std::vec::IntoIter<(&'static str, AttributeValue<'a>)>);
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = (&'static str, AttributeValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Attributes<'_> {}
+
+/// Collects the pairs with a value.
+#[derive(Default)]
+struct Builder<'a>(Vec<(&'static str, AttributeValue<'a>)>);
+
+impl<'a> Builder<'a> {
+    fn str(&mut self, name: &'static str, value: Option<&'a str>) {
+        if let Some(value) = value {
+            self.0
+                .push((name, AttributeValue::String(Cow::Borrowed(value))));
+        }
+    }
+
+    fn string(&mut self, name: &'static str, value: Option<String>) {
+        if let Some(value) = value {
+            self.0
+                .push((name, AttributeValue::String(Cow::Owned(value))));
+        }
+    }
+
+    fn int(&mut self, name: &'static str, value: Option<u32>) {
+        if let Some(value) = value {
+            self.0.push((name, AttributeValue::Int(value.into())));
+        }
+    }
+
+    fn double(&mut self, name: &'static str, value: Option<f64>) {
+        if let Some(value) = value {
+            self.0.push((name, AttributeValue::Double(value)));
+        }
+    }
+
+    fn geo_point(&mut self, name: &'static str, lat: Option<f64>, lon: Option<f64>) {
+        if let (Some(lat), Some(lon)) = (lat, lon) {
+            self.0.push((name, AttributeValue::GeoPoint { lat, lon }));
+        }
+    }
+
+    fn build(self) -> Attributes<'a> {
+        Attributes(self.0.into_iter())
+    }
+}
+
+/// The location fields City and Enterprise have in common, names already
+/// in the preferred language.
+#[derive(Default)]
+struct Geo<'a> {
+    continent_code: Option<&'a str>,
+    continent_name: Option<&'a str>,
+    country_iso_code: Option<&'a str>,
+    country_name: Option<&'a str>,
+    // of the most specific subdivision
+    subdivision_iso_code: Option<&'a str>,
+    subdivision_name: Option<&'a str>,
+    city_name: Option<&'a str>,
+    postal_code: Option<&'a str>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    time_zone: Option<&'a str>,
+    autonomous_system_number: Option<u32>,
+    autonomous_system_organization: Option<&'a str>,
+    mobile_country_code: Option<&'a str>,
+    mobile_network_code: Option<&'a str>,
+    isp: Option<&'a str>,
+}
+
+fn best<'a, L: AsRef<str>>(names: Option<&Names<'a>>, languages: &[L]) -> Option<&'a str> {
+    names?.best(languages)
+}
+
+impl<'a> Geo<'a> {
+    // ISO 3166-2, e.g. `GB-ENG`
+    fn region_iso_code(&self) -> Option<String> {
+        Some(format!(
+            "{}-{}",
+            self.country_iso_code?, self.subdivision_iso_code?
+        ))
+    }
+
+    fn otel(&self) -> Attributes<'a> {
+        let mut attributes = Builder::default();
+        attributes.str("geo.continent.code", self.continent_code);
+        attributes.str("geo.country.iso_code", self.country_iso_code);
+        attributes.string("geo.region.iso_code", self.region_iso_code());
+        attributes.str("geo.locality.name", self.city_name);
+        attributes.str("geo.postal_code", self.postal_code);
+        if let (Some(lat), Some(lon)) = (self.latitude, self.longitude) {
+            attributes.double("geo.location.lat", Some(lat));
+            attributes.double("geo.location.lon", Some(lon));
+        }
+        carrier(
+            &mut attributes,
+            self.mobile_country_code,
+            self.mobile_network_code,
+            self.isp,
+        );
+        attributes.build()
+    }
+
+    fn ecs(&self) -> Attributes<'a> {
+        let mut fields = Builder::default();
+        fields.str("source.geo.continent_code", self.continent_code);
+        fields.str("source.geo.continent_name", self.continent_name);
+        fields.str("source.geo.country_iso_code", self.country_iso_code);
+        fields.str("source.geo.country_name", self.country_name);
+        fields.string("source.geo.region_iso_code", self.region_iso_code());
+        fields.str("source.geo.region_name", self.subdivision_name);
+        fields.str("source.geo.city_name", self.city_name);
+        fields.str("source.geo.postal_code", self.postal_code);
+        fields.geo_point("source.geo.location", self.latitude, self.longitude);
+        fields.str("source.geo.timezone", self.time_zone);
+        autonomous_system(
+            &mut fields,
+            self.autonomous_system_number,
+            self.autonomous_system_organization,
+        );
+        fields.build()
+    }
+}
+
+// the ISP of a mobile network is its carrier
+fn carrier<'a>(
+    attributes: &mut Builder<'a>,
+    mobile_country_code: Option<&'a str>,
+    mobile_network_code: Option<&'a str>,
+    isp: Option<&'a str>,
+) {
+    attributes.str("network.carrier.mcc", mobile_country_code);
+    attributes.str("network.carrier.mnc", mobile_network_code);
+    if mobile_network_code.is_some() {
+        attributes.str("network.carrier.name", isp);
+    }
+}
+
+fn autonomous_system<'a>(
+    fields: &mut Builder<'a>,
+    number: Option<u32>,
+    organization: Option<&'a str>,
+) {
+    fields.int("source.as.number", number);
+    fields.str("source.as.organization.name", organization);
+}
+
+// City and Enterprise have the same fields, of different models
+macro_rules! geo_records {
+    ($($record:ident),*) => {
+        $(
+            impl<'a> $record<'a> {
+                /// The OpenTelemetry `geo.*` and `network.carrier.*`
+                /// attributes, the city name in the first of `languages`
+                /// available.
+                pub fn otel_attributes<L: AsRef<str>>(&self, languages: &[L]) -> Attributes<'a> {
+                    self.geo(languages).otel()
+                }
+
+                /// The ECS `source.geo.*` and `source.as.*` fields, names in
+                /// the first of `languages` available.
+                pub fn ecs_fields<L: AsRef<str>>(&self, languages: &[L]) -> Attributes<'a> {
+                    self.geo(languages).ecs()
+                }
+
+                fn geo<L: AsRef<str>>(&self, languages: &[L]) -> Geo<'a> {
+                    let mut geo = Geo::default();
+                    if let Some(continent) = &self.continent {
+                        geo.continent_code = continent.code;
+                        geo.continent_name = best(continent.names.as_ref(), languages);
+                    }
+                    if let Some(country) = &self.country {
+                        geo.country_iso_code = country.iso_code;
+                        geo.country_name = best(country.names.as_ref(), languages);
+                    }
+                    if let Some(subdivision) = self.subdivisions.as_ref().and_then(|s| s.last()) {
+                        geo.subdivision_iso_code = subdivision.iso_code;
+                        geo.subdivision_name = best(subdivision.names.as_ref(), languages);
+                    }
+                    geo.city_name = self.city_name(languages);
+                    geo.postal_code = self.postal.as_ref().and_then(|postal| postal.code);
+                    if let Some(location) = &self.location {
+                        geo.latitude = location.latitude;
+                        geo.longitude = location.longitude;
+                        geo.time_zone = location.time_zone;
+                    }
+                    if let Some(traits) = &self.traits {
+                        geo.autonomous_system_number = traits.autonomous_system_number;
+                        geo.autonomous_system_organization = traits.autonomous_system_organization;
+                        geo.mobile_country_code = traits.mobile_country_code;
+                        geo.mobile_network_code = traits.mobile_network_code;
+                        geo.isp = traits.isp;
+                    }
+                    geo
+                }
+            }
+        )*
+    };
+}
+
+geo_records!(City, Enterprise);
+
+impl<'a> Asn<'a> {
+    /// The ECS `source.as.*` fields. OpenTelemetry has no attributes for
+    /// autonomous systems.
+    pub fn ecs_fields(&self) -> Attributes<'a> {
+        let mut fields = Builder::default();
+        autonomous_system(
+            &mut fields,
+            self.autonomous_system_number,
+            self.autonomous_system_organization,
+        );
+        fields.build()
+    }
+}
+
+impl<'a> Isp<'a> {
+    /// The OpenTelemetry `network.carrier.*` attributes, of mobile networks
+    /// only.
+    pub fn otel_attributes(&self) -> Attributes<'a> {
+        let mut attributes = Builder::default();
+        carrier(
+            &mut attributes,
+            self.mobile_country_code,
+            self.mobile_network_code,
+            self.isp,
+        );
+        attributes.build()
+    }
+
+    /// The ECS `source.as.*` fields.
+    pub fn ecs_fields(&self) -> Attributes<'a> {
+        let mut fields = Builder::default();
+        autonomous_system(
+            &mut fields,
+            self.autonomous_system_number,
+            self.autonomous_system_organization,
+        );
+        fields.build()
+    }
+}
@@ -0,0 +1,133 @@
+use crate::decode::{
+    bytes_to_f32, bytes_to_f64, bytes_to_integer, read_bytes, read_control, read_pointer, read_str,
+    Data, Decoder, DATA_TYPE_BOOL, DATA_TYPE_BYTES, DATA_TYPE_FLOAT32, DATA_TYPE_FLOAT64,
+    DATA_TYPE_INT32, DATA_TYPE_MAP, DATA_TYPE_POINTER, DATA_TYPE_SLICE, DATA_TYPE_STRING,
+    DATA_TYPE_UINT128, DATA_TYPE_UINT16, DATA_TYPE_UINT32, DATA_TYPE_UINT64, MAX_DEPTH,
+};
+use crate::Error;
+
+/// Any value of the data section, decoded along with its MaxMind DB type.
+/// It's useful for databases without a dedicated model.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    String(&'a str),
+    Double(f64),
+    Bytes(&'a [u8]),
+    Uint16(u16),
+    Uint32(u32),
+    Map(Vec<(&'a str, Value<'a>)>),
+    Int32(i32),
+    Uint64(u64),
+    Uint128(u128),
+    Array(Vec<Value<'a>>),
+    Bool(bool),
+    Float(f32),
+}
+
+impl<'a> Value<'a> {
+    /// Get the value of `key` if this is a map.
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        match self {
+            Value::Map(map) => map.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for Value<'a> {
+    fn decode(buf: Data<'a>, offset: &mut usize) -> Result<Self, Error> {
+        decode_nested(buf, offset, 0)
+    }
+
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        decode_map(buf, offset, size, 0)
+    }
+}
+
+fn decode_nested<'a>(buf: Data<'a>, offset: &mut usize, depth: usize) -> Result<Value<'a>, Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::InvalidOffset);
+    }
+
+    let start = *offset;
+    let (data_type, size) = read_control(buf.bytes(), offset)?;
+    match data_type {
+        DATA_TYPE_POINTER => {
+            let start = read_pointer(buf.bytes(), offset, size)?;
+            let offset = &mut { start };
+            let (data_type, size) = read_control(buf.bytes(), offset)?;
+            match data_type {
+                DATA_TYPE_POINTER => Err(Error::InvalidDataType(data_type)),
+                _ => decode_value(buf, start, offset, data_type, size, depth),
+            }
+        }
+        _ => decode_value(buf, start, offset, data_type, size, depth),
+    }
+}
+
+fn decode_map<'a>(
+    buf: Data<'a>,
+    offset: &mut usize,
+    size: usize,
+    depth: usize,
+) -> Result<Value<'a>, Error> {
+    let mut map = Vec::with_capacity(size);
+    for _ in 0..size {
+        map.push((
+            read_str(buf, offset)?,
+            decode_nested(buf, offset, depth + 1)?,
+        ));
+    }
+
+    Ok(Value::Map(map))
+}
+
+// `start` is the offset of the control byte of the value
+fn decode_value<'a>(
+    buf: Data<'a>,
+    start: usize,
+    offset: &mut usize,
+    data_type: u8,
+    size: usize,
+    depth: usize,
+) -> Result<Value<'a>, Error> {
+    let value = match data_type {
+        DATA_TYPE_STRING => Value::String(buf.read_string(start, offset, size)?),
+        DATA_TYPE_FLOAT64 => Value::Double(bytes_to_f64(read_bytes(buf.bytes(), offset, size)?)?),
+        DATA_TYPE_BYTES => Value::Bytes(read_bytes(buf.bytes(), offset, size)?),
+        DATA_TYPE_UINT16 => {
+            let (_, value) = bytes_to_integer(data_type, read_bytes(buf.bytes(), offset, size)?)?;
+            Value::Uint16(value as u16)
+        }
+        DATA_TYPE_UINT32 => {
+            let (_, value) = bytes_to_integer(data_type, read_bytes(buf.bytes(), offset, size)?)?;
+            Value::Uint32(value as u32)
+        }
+        DATA_TYPE_INT32 => {
+            let (_, value) = bytes_to_integer(data_type, read_bytes(buf.bytes(), offset, size)?)?;
+            Value::Int32(value as u32 as i32)
+        }
+        DATA_TYPE_UINT64 => {
+            let (_, value) = bytes_to_integer(data_type, read_bytes(buf.bytes(), offset, size)?)?;
+            Value::Uint64(value as u64)
+        }
+        DATA_TYPE_UINT128 => {
+            let (_, value) = bytes_to_integer(data_type, read_bytes(buf.bytes(), offset, size)?)?;
+            Value::Uint128(value)
+        }
+        DATA_TYPE_MAP => decode_map(buf, offset, size, depth)?,
+        DATA_TYPE_SLICE => {
+            let mut array = Vec::with_capacity(size);
+            for _ in 0..size {
+                array.push(decode_nested(buf, offset, depth + 1)?);
+            }
+
+            Value::Array(array)
+        }
+        DATA_TYPE_BOOL => Value::Bool(size != 0),
+        DATA_TYPE_FLOAT32 => Value::Float(bytes_to_f32(read_bytes(buf.bytes(), offset, size)?)?),
+        _ => return Err(Error::InvalidDataType(data_type)),
+    };
+
+    Ok(value)
+}
@@ -0,0 +1,225 @@
+//! SAX style traversal of the values of the data section.
+
+use std::net::IpAddr;
+
+use crate::decode::{
+    bytes_to_f32, bytes_to_f64, bytes_to_integer, read_bytes, read_control, read_pointer, read_str,
+    skip_value, Data, DATA_TYPE_BOOL, DATA_TYPE_BYTES, DATA_TYPE_FLOAT32, DATA_TYPE_FLOAT64,
+    DATA_TYPE_INT32, DATA_TYPE_MAP, DATA_TYPE_POINTER, DATA_TYPE_SLICE, DATA_TYPE_STRING,
+    DATA_TYPE_UINT128, DATA_TYPE_UINT16, DATA_TYPE_UINT32, DATA_TYPE_UINT64, MAX_DEPTH,
+};
+use crate::{Error, Reader};
+
+/// Whether to walk into the value a [`Visitor`] was just told about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Walk {
+    Enter,
+    Skip,
+}
+
+/// Receives the values of a record in order, as [`Reader::lookup_visit`]
+/// walks it. Pointers are followed transparently and nothing is allocated.
+/// Every method does nothing by default.
+///
+/// ```no_run
+/// use maxminddb::{Error, Reader, Visitor, Walk};
+///
+/// // count the strings of a record, names left out
+/// #[derive(Default)]
+/// struct Strings(usize);
+///
+/// impl<'a> Visitor<'a> for Strings {
+///     fn visit_key(&mut self, key: &'a str) -> Result<Walk, Error> {
+///         Ok(if key == "names" { Walk::Skip } else { Walk::Enter })
+///     }
+///
+///     fn visit_str(&mut self, _value: &'a str) -> Result<(), Error> {
+///         self.0 += 1;
+///         Ok(())
+///     }
+/// }
+///
+/// let reader = Reader::open_file("GeoLite2-City.mmdb")?;
+/// let mut strings = Strings::default();
+/// reader.lookup_visit("89.160.20.128".parse().unwrap(), &mut strings)?;
+/// # Ok::<(), Error>(())
+/// ```
+#[allow(unused_variables)]
+pub trait Visitor<'a> {
+    /// A map of `len` entries starts. [`Walk::Skip`] skips all of it,
+    /// `visit_map_end` included.
+    fn visit_map_start(&mut self, len: usize) -> Result<Walk, Error> {
+        Ok(Walk::Enter)
+    }
+
+    /// The key of the next entry of the map, [`Walk::Skip`] skips its
+    /// value.
+    fn visit_key(&mut self, key: &'a str) -> Result<Walk, Error> {
+        Ok(Walk::Enter)
+    }
+
+    fn visit_map_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// An array of `len` elements starts. [`Walk::Skip`] skips all of it,
+    /// `visit_array_end` included.
+    fn visit_array_start(&mut self, len: usize) -> Result<Walk, Error> {
+        Ok(Walk::Enter)
+    }
+
+    fn visit_array_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &'a str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn visit_bytes(&mut self, value: &'a [u8]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// An uint16, given to `visit_u64` by default.
+    fn visit_u16(&mut self, value: u16) -> Result<(), Error> {
+        self.visit_u64(value.into())
+    }
+
+    /// An uint32, given to `visit_u64` by default.
+    fn visit_u32(&mut self, value: u32) -> Result<(), Error> {
+        self.visit_u64(value.into())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn visit_i32(&mut self, value: i32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A float, given to `visit_f64` by default.
+    fn visit_f32(&mut self, value: f32) -> Result<(), Error> {
+        self.visit_f64(value.into())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, S: AsRef<[u8]>> Reader<S> {
+    /// Lookup the address and walk its record with `visitor`.
+    pub fn lookup_visit<V: Visitor<'a> + ?Sized>(
+        &'a self,
+        addr: IpAddr,
+        visitor: &mut V,
+    ) -> Result<(), Error> {
+        let mut offset = self.find_value(addr)?;
+        walk(self.data(), &mut offset, visitor)
+    }
+}
+
+/// Walk the value at `offset` with `visitor`, and move `offset` past it.
+pub(crate) fn walk<'a, V: Visitor<'a> + ?Sized>(
+    buf: Data<'a>,
+    offset: &mut usize,
+    visitor: &mut V,
+) -> Result<(), Error> {
+    walk_nested(buf, offset, 0, visitor)
+}
+
+fn walk_nested<'a, V: Visitor<'a> + ?Sized>(
+    buf: Data<'a>,
+    offset: &mut usize,
+    depth: usize,
+    visitor: &mut V,
+) -> Result<(), Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::InvalidOffset);
+    }
+
+    let start = *offset;
+    let (data_type, size) = read_control(buf.bytes(), offset)?;
+    if data_type == DATA_TYPE_POINTER {
+        // NOTE: a pointer to a pointer is invalid, `walk_data` rejects it
+        let start = read_pointer(buf.bytes(), offset, size)?;
+        let offset = &mut { start };
+        let (data_type, size) = read_control(buf.bytes(), offset)?;
+        return walk_data(buf, start, offset, data_type, size, depth, visitor);
+    }
+
+    walk_data(buf, start, offset, data_type, size, depth, visitor)
+}
+
+// `start` is the offset of the control byte of the value
+fn walk_data<'a, V: Visitor<'a> + ?Sized>(
+    buf: Data<'a>,
+    start: usize,
+    offset: &mut usize,
+    data_type: u8,
+    size: usize,
+    depth: usize,
+    visitor: &mut V,
+) -> Result<(), Error> {
+    match data_type {
+        DATA_TYPE_MAP => {
+            if visitor.visit_map_start(size)? == Walk::Skip {
+                for _ in 0..size * 2 {
+                    skip_value(buf.bytes(), offset)?;
+                }
+                return Ok(());
+            }
+
+            for _ in 0..size {
+                match visitor.visit_key(read_str(buf, offset)?)? {
+                    Walk::Enter => walk_nested(buf, offset, depth + 1, visitor)?,
+                    Walk::Skip => skip_value(buf.bytes(), offset)?,
+                }
+            }
+            visitor.visit_map_end()
+        }
+        DATA_TYPE_SLICE => {
+            if visitor.visit_array_start(size)? == Walk::Skip {
+                for _ in 0..size {
+                    skip_value(buf.bytes(), offset)?;
+                }
+                return Ok(());
+            }
+
+            for _ in 0..size {
+                walk_nested(buf, offset, depth + 1, visitor)?;
+            }
+            visitor.visit_array_end()
+        }
+        DATA_TYPE_STRING => visitor.visit_str(buf.read_string(start, offset, size)?),
+        DATA_TYPE_FLOAT64 => {
+            visitor.visit_f64(bytes_to_f64(read_bytes(buf.bytes(), offset, size)?)?)
+        }
+        DATA_TYPE_FLOAT32 => {
+            visitor.visit_f32(bytes_to_f32(read_bytes(buf.bytes(), offset, size)?)?)
+        }
+        DATA_TYPE_BYTES => visitor.visit_bytes(read_bytes(buf.bytes(), offset, size)?),
+        DATA_TYPE_UINT16 | DATA_TYPE_UINT32 | DATA_TYPE_INT32 | DATA_TYPE_UINT64
+        | DATA_TYPE_UINT128 => {
+            let (_, value) = bytes_to_integer(data_type, read_bytes(buf.bytes(), offset, size)?)?;
+            match data_type {
+                DATA_TYPE_UINT16 => visitor.visit_u16(value as u16),
+                DATA_TYPE_UINT32 => visitor.visit_u32(value as u32),
+                DATA_TYPE_INT32 => visitor.visit_i32(value as u32 as i32),
+                DATA_TYPE_UINT64 => visitor.visit_u64(value as u64),
+                _ => visitor.visit_u128(value),
+            }
+        }
+        DATA_TYPE_BOOL => visitor.visit_bool(size != 0),
+        _ => Err(Error::InvalidDataType(data_type)),
+    }
+}
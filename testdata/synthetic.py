@@ -0,0 +1,497 @@
+"""Write the synthetic test databases, `python3 testdata/synthetic.py`.
+
+The GeoIP2 and GeoLite2 test databases come from MaxMind. The ones written
+here are named `Synthetic-*` so they're not mistaken for MaxMind's, they
+cover products and data types missing from them and all the data is made up.
+
+Values are tagged tuples: ("utf8_string", "x"), ("uint32", 5), ("map", [(k, v), ...]),
+("array", [...]), ("bytes", b"..."), ("double", 1.0), ("float", 1.0), ("int32", -1),
+("uint16", 1), ("uint64", 1), ("uint128", 1), ("boolean", True).
+Map keys are plain python strings.
+"""
+import ipaddress
+import os
+import struct
+
+TYPES = {
+    "pointer": 1, "utf8_string": 2, "double": 3, "bytes": 4, "uint16": 5, "uint32": 6,
+    "map": 7, "int32": 8, "uint64": 9, "uint128": 10, "array": 11, "boolean": 14, "float": 15,
+}
+
+
+def control(type_name, size):
+    t = TYPES[type_name]
+    if size < 29:
+        bits, extra = size, b""
+    elif size < 285:
+        bits, extra = 29, bytes([size - 29])
+    elif size < 65821:
+        bits, extra = 30, (size - 285).to_bytes(2, "big")
+    else:
+        bits, extra = 31, (size - 65821).to_bytes(3, "big")
+    if t < 8:
+        return bytes([t << 5 | bits]) + extra
+    return bytes([bits, t - 7]) + extra
+
+
+def pointer(p):
+    if p < 2048:
+        return bytes([0x20 | (p >> 8), p & 0xFF])
+    if p < 526336:
+        v = p - 2048
+        return bytes([0x28 | (v >> 16)]) + (v & 0xFFFF).to_bytes(2, "big")
+    if p < 134744064:
+        v = p - 526336
+        return bytes([0x30 | (v >> 24)]) + (v & 0xFFFFFF).to_bytes(3, "big")
+    return bytes([0x38]) + p.to_bytes(4, "big")
+
+
+def int_bytes(v):
+    n = (v.bit_length() + 7) // 8
+    return v.to_bytes(n, "big") if n else b""
+
+
+class Data:
+    def __init__(self, dedup=True):
+        self.buf = bytearray()
+        self.cache = {}
+        self.dedup = dedup
+
+    def key(self, value):
+        return repr(value)
+
+    def add(self, value):
+        """Append value, returns its offset, reusing equal values."""
+        k = self.key(value)
+        if self.dedup and k in self.cache:
+            return self.cache[k]
+        off = len(self.buf)
+        self.write(value)
+        self.cache[k] = off
+        return off
+
+    def write(self, value):
+        t, v = value
+        if t == "array":
+            self.buf += control(t, len(v))
+            for item in v:
+                self.member(item)
+        elif t == "map":
+            self.buf += control(t, len(v))
+            for k, item in v:
+                self.member(("utf8_string", k))
+                self.member(item)
+        else:
+            self.buf += self.encode(value)
+
+    def member(self, value):
+        # strings and containers already written are referenced by pointer
+        k = self.key(value)
+        if self.dedup and k in self.cache:
+            self.buf += pointer(self.cache[k])
+            return
+        off = len(self.buf)
+        self.write(value)
+        if value[0] in ("utf8_string", "map", "array") and len(self.buf) - off > 2:
+            self.cache[k] = off
+
+    def encode(self, value):
+        t, v = value
+        if t == "utf8_string":
+            b = v.encode()
+            return control(t, len(b)) + b
+        if t == "bytes":
+            return control(t, len(v)) + v
+        if t == "double":
+            return control(t, 8) + struct.pack(">d", v)
+        if t == "float":
+            return control(t, 4) + struct.pack(">f", v)
+        if t in ("uint16", "uint32", "uint64", "uint128"):
+            b = int_bytes(v)
+            return control(t, len(b)) + b
+        if t == "int32":
+            b = int_bytes(v & 0xFFFFFFFF)
+            return control(t, len(b)) + b
+        if t == "boolean":
+            return control(t, 1 if v else 0)
+        raise ValueError(t)
+
+
+class Node:
+    __slots__ = ("children", "index")
+
+    def __init__(self):
+        self.children = [None, None]  # Node, ("data", value) or None
+        self.index = None
+
+
+def build(networks, ip_version=6, record_size=28, database_type="Test",
+          languages=("en",), description="Test database", alias_ipv4=True,
+          build_epoch=1700000000, dedup=True):
+    """networks: list of (cidr string, value)."""
+    width = 128 if ip_version == 6 else 32
+    root = Node()
+    data = Data(dedup)
+
+    for cidr, value in networks:
+        net = ipaddress.ip_network(cidr)
+        addr = int(net.network_address)
+        plen = net.prefixlen
+        if net.version == 4 and ip_version == 6:
+            plen += 96
+        # IPv4 networks of an IPv6 tree live in ::/96
+        bits = addr
+        node = root
+        for i in range(plen - 1):
+            bit = (bits >> (width - 1 - i)) & 1
+            child = node.children[bit]
+            if child is None:
+                child = Node()
+                node.children[bit] = child
+            elif not isinstance(child, Node):
+                # split a leaf
+                new = Node()
+                new.children = [child, child]
+                node.children[bit] = new
+                child = new
+            node = child
+        bit = (bits >> (width - 1 - (plen - 1))) & 1
+        node.children[bit] = ("data", value)
+
+    if ip_version == 6 and alias_ipv4:
+        # ::/96 is the IPv4 subtree
+        node = root
+        for _ in range(96):
+            if node.children[0] is None:
+                node.children[0] = Node()
+            node = node.children[0]
+        ipv4 = node
+        for prefix, length in ((0xFFFF << 32, 96), (0x2002 << 112, 16)):
+            node = root
+            for i in range(length - 1):
+                bit = (prefix >> (127 - i)) & 1
+                if node.children[bit] is None or not isinstance(node.children[bit], Node):
+                    node.children[bit] = Node()
+                node = node.children[bit]
+            bit = (prefix >> (127 - (length - 1))) & 1
+            node.children[bit] = ipv4
+
+    # number nodes breadth first
+    order = []
+    queue = [root]
+    seen = set()
+    while queue:
+        node = queue.pop(0)
+        if id(node) in seen:
+            continue
+        seen.add(id(node))
+        node.index = len(order)
+        order.append(node)
+        for child in node.children:
+            if isinstance(child, Node):
+                queue.append(child)
+    node_count = len(order)
+
+    def record(child):
+        if child is None:
+            return node_count
+        if isinstance(child, Node):
+            return child.index
+        return node_count + 16 + data.add(child[1])
+
+    tree = bytearray()
+    for node in order:
+        left, right = record(node.children[0]), record(node.children[1])
+        if record_size == 24:
+            tree += left.to_bytes(3, "big") + right.to_bytes(3, "big")
+        elif record_size == 28:
+            tree += (left & 0xFFFFFF).to_bytes(3, "big")
+            tree += bytes([((left >> 24) << 4) | (right >> 24)])
+            tree += (right & 0xFFFFFF).to_bytes(3, "big")
+        else:
+            tree += left.to_bytes(4, "big") + right.to_bytes(4, "big")
+
+    meta = Data(dedup=False)
+    metadata = ("map", [
+        ("binary_format_major_version", ("uint16", 2)),
+        ("binary_format_minor_version", ("uint16", 0)),
+        ("build_epoch", ("uint64", build_epoch)),
+        ("database_type", ("utf8_string", database_type)),
+        ("description", ("map", [("en", ("utf8_string", description))])),
+        ("ip_version", ("uint16", ip_version)),
+        ("languages", ("array", [("utf8_string", l) for l in languages])),
+        ("node_count", ("uint32", node_count)),
+        ("record_size", ("uint16", record_size)),
+    ])
+    meta.add(metadata)
+
+    return bytes(tree) + b"\x00" * 16 + bytes(data.buf) + b"\xab\xcd\xefMaxMind.com" + bytes(meta.buf)
+
+
+def s(v):
+    return ("utf8_string", v)
+
+
+def names(**kw):
+    return ("map", [(k.replace("_", "-"), s(v)) for k, v in kw.items()])
+
+
+def uint16(v):
+    return ("uint16", v)
+
+
+def uint32(v):
+    return ("uint32", v)
+
+
+def double(v):
+    return ("double", v)
+
+
+def boolean(v):
+    return ("boolean", v)
+
+
+def record(**kw):
+    return ("map", list(kw.items()))
+
+
+def write(name, networks, **kw):
+    path = os.path.join(os.path.dirname(os.path.abspath(__file__)), name)
+    with open(path, "wb") as f:
+        f.write(build(networks, **kw))
+
+
+def decoder():
+    # written here to follow MaxMind-DB-test-decoder.mmdb of the MaxMind-DB
+    # repository from its documented values, not generated from that file or
+    # checked against it
+    all_types = record(**{
+        "array": ("array", [uint32(1), uint32(2), uint32(3)]),
+        "boolean": boolean(True),
+        "bytes": ("bytes", bytes([0, 0, 0, 42])),
+        "double": double(42.123456),
+        "float": ("float", 1.1),
+        "int32": ("int32", -268435456),
+        "map": record(mapX=record(
+            arrayX=("array", [uint32(7), uint32(8), uint32(9)]),
+            utf8_stringX=s("hello"),
+        )),
+        "uint128": ("uint128", 1329227995784915872903807060280344576),
+        "uint16": uint16(100),
+        "uint32": uint32(268435456),
+        "uint64": ("uint64", 1152921504606846976),
+        "utf8_string": s("unicode! \u262f - \u266b"),
+    })
+    zero = record(**{
+        "array": ("array", []),
+        "boolean": boolean(False),
+        "bytes": ("bytes", b""),
+        "double": double(0.0),
+        "float": ("float", 0.0),
+        "int32": ("int32", 0),
+        "map": ("map", []),
+        "uint128": ("uint128", 0),
+        "uint16": uint16(0),
+        "uint32": uint32(0),
+        "uint64": ("uint64", 0),
+        "utf8_string": s(""),
+    })
+    max_values = record(**{
+        "array": ("array", []),
+        "boolean": boolean(True),
+        "bytes": ("bytes", b"\xff" * 4),
+        "double": double(float("inf")),
+        "float": ("float", float("inf")),
+        "int32": ("int32", 2**31 - 1),
+        "map": ("map", []),
+        "uint128": ("uint128", 2**128 - 1),
+        "uint16": uint16(2**16 - 1),
+        "uint32": uint32(2**32 - 1),
+        "uint64": ("uint64", 2**64 - 1),
+        "utf8_string": s(""),
+    })
+
+    networks = [(net, all_types) for net in (
+        "1.1.1.0/24", "2.2.0.0/16", "3.0.0.0/8", "4.5.6.7/32", "abcd::/64", "1000::1234:0/112",
+    )]
+    networks += [("0.0.0.0/32", zero), ("255.255.255.255/32", max_values)]
+    write("Synthetic-Decoder-Test.mmdb", networks, record_size=24,
+          database_type="Synthetic-Decoder-Test",
+          description="Synthetic test database, contains every MaxMind DB data type")
+
+
+def anonymous_plus():
+    write("Synthetic-Anonymous-Plus-Test.mmdb", [
+        ("1.2.0.0/16", record(
+            anonymizer_confidence=uint16(30),
+            is_anonymous=boolean(True),
+            is_anonymous_vpn=boolean(True),
+            network_last_seen=s("2025-04-14"),
+            provider_name=s("foo"),
+        )),
+        ("1.124.213.1/32", record(
+            anonymizer_confidence=uint16(75),
+            is_anonymous=boolean(True),
+            is_anonymous_vpn=boolean(True),
+            is_residential_proxy=boolean(True),
+            network_last_seen=s("2025-04-14"),
+            provider_name=s("bar"),
+        )),
+        ("81.2.69.0/24", record(
+            anonymizer_confidence=uint16(99),
+            is_anonymous=boolean(True),
+            is_hosting_provider=boolean(True),
+            is_public_proxy=boolean(True),
+            is_tor_exit_node=boolean(True),
+        )),
+    ], database_type="GeoIP-Anonymous-Plus", description="GeoIP Anonymous Plus synthetic test database")
+
+
+def ip_risk():
+    write("Synthetic-IP-Risk-Test.mmdb", [
+        ("6.1.2.0/24", record(ip_risk=double(0.01))),
+        ("214.2.3.0/24", record(
+            ip_risk=double(25.0),
+            is_anonymous=boolean(True),
+            is_anonymous_vpn=boolean(True),
+            is_hosting_provider=boolean(True),
+        )),
+        ("81.2.69.0/24", record(
+            ip_risk=double(99.0),
+            is_anonymous=boolean(True),
+            is_public_proxy=boolean(True),
+            is_residential_proxy=boolean(True),
+            is_tor_exit_node=boolean(True),
+        )),
+    ], database_type="GeoIP2-IP-Risk", description="GeoIP2 IP Risk synthetic test database")
+
+
+def static_ip_score():
+    write("Synthetic-Static-IP-Score-Test.mmdb", [
+        ("1.2.0.0/16", record(score=double(0.34))),
+        ("1.3.0.0/16", record(score=double(0.0))),
+        ("2a02:ff80::/29", record(score=double(99.0))),
+    ], database_type="GeoIP2-Static-IP-Score", description="GeoIP2 Static IP Score synthetic test database")
+
+
+def user_count():
+    write("Synthetic-User-Count-Test.mmdb", [
+        ("1.2.3.0/24", record(ipv4_24=uint32(4), ipv4_32=uint32(3))),
+        ("1.2.4.0/24", record(ipv4_24=uint32(0), ipv4_32=uint32(0))),
+        ("2a02:ff80::/29", record(ipv6_32=uint32(110), ipv6_48=uint32(12), ipv6_64=uint32(7))),
+    ], database_type="GeoIP2-User-Count", description="GeoIP2 User Count synthetic test database")
+
+
+def density_income():
+    write("Synthetic-DensityIncome-Test.mmdb", [
+        ("5.83.124.0/22", record(average_income=uint32(32323), population_density=uint32(1232))),
+        ("81.2.69.0/24", record(average_income=uint32(45000), population_density=uint32(5374))),
+    ], database_type="GeoIP2-DensityIncome", description="GeoIP2 DensityIncome synthetic test database")
+
+
+def city_plus():
+    united_kingdom = record(
+        confidence=uint16(99),
+        geoname_id=uint32(2635167),
+        is_in_european_union=boolean(False),
+        iso_code=s("GB"),
+        names=names(en="United Kingdom", de="Vereinigtes Königreich"),
+    )
+    write("Synthetic-City-Plus-Test.mmdb", [
+        ("81.2.69.0/24", record(
+            city=record(
+                confidence=uint16(60),
+                geoname_id=uint32(2643743),
+                names=names(de="London", en="London", zh_CN="伦敦"),
+            ),
+            continent=record(code=s("EU"), geoname_id=uint32(6255148), names=names(en="Europe")),
+            country=united_kingdom,
+            location=record(
+                accuracy_radius=uint16(10),
+                average_income=uint32(45000),
+                latitude=double(51.5142),
+                longitude=double(-0.0931),
+                metro_code=uint16(0),
+                population_density=uint32(5374),
+                time_zone=s("Europe/London"),
+            ),
+            postal=record(code=s("EC2V"), confidence=uint16(40)),
+            registered_country=record(
+                geoname_id=uint32(2635167),
+                is_in_european_union=boolean(False),
+                iso_code=s("GB"),
+                names=names(en="United Kingdom"),
+            ),
+            represented_country=record(
+                geoname_id=uint32(6252001),
+                iso_code=s("US"),
+                names=names(en="United States"),
+                type=s("military"),
+            ),
+            subdivisions=("array", [record(
+                confidence=uint16(80),
+                geoname_id=uint32(6269131),
+                iso_code=s("ENG"),
+                names=names(en="England"),
+            )]),
+            traits=record(
+                autonomous_system_number=uint32(20712),
+                autonomous_system_organization=s("Andrews & Arnold Ltd"),
+                connection_type=s("Corporate"),
+                domain=s("in-addr.arpa"),
+                ip_address=s("81.2.69.142"),
+                is_anonymous=boolean(True),
+                is_anonymous_proxy=boolean(False),
+                is_anonymous_vpn=boolean(True),
+                is_anycast=boolean(True),
+                is_hosting_provider=boolean(True),
+                is_legitimate_proxy=boolean(True),
+                is_public_proxy=boolean(True),
+                is_residential_proxy=boolean(True),
+                is_satellite_provider=boolean(False),
+                is_tor_exit_node=boolean(True),
+                isp=s("Andrews & Arnold Ltd"),
+                mobile_country_code=s("234"),
+                mobile_network_code=s("10"),
+                network=s("81.2.69.0/24"),
+                organization=s("STONEHOUSE office network"),
+                static_ip_score=double(0.34),
+                user_count=uint32(2),
+                user_type=s("government"),
+            ),
+        )),
+    ], database_type="GeoIP2-City-Plus", languages=("de", "en", "zh-CN"), description="GeoIP2 City Plus synthetic test database, every field of the City and Insights schema")
+
+
+def scalars():
+    # records that are not maps, like a database of just ASNs or country codes
+    write("Synthetic-Scalars-Test.mmdb", [
+        ("1.0.0.0/24", uint32(13335)),
+        ("2.0.0.0/24", s("US")),
+        ("3.0.0.0/24", ("array", [s("US"), s("CA")])),
+        ("4.0.0.0/24", boolean(True)),
+        ("5.0.0.0/24", record(autonomous_system_number=uint32(13335))),
+    ], ip_version=4, record_size=24, database_type="Synthetic-Scalars-Test",
+          description="MaxMind DB test database whose records are not maps")
+
+
+def shallow_ipv6():
+    # an IPv6 tree of one node, `::/96` ends right away
+    write("Synthetic-Shallow-IPv6-Test.mmdb", [
+        ("::/1", record(half=uint16(0))),
+        ("8000::/1", record(half=uint16(1))),
+    ], record_size=24, alias_ipv4=False, database_type="Synthetic-Shallow-IPv6-Test",
+          description="IPv6 test database whose tree is a single node")
+
+
+if __name__ == "__main__":
+    decoder()
+    anonymous_plus()
+    ip_risk()
+    static_ip_score()
+    user_count()
+    density_income()
+    city_plus()
+    scalars()
+    shallow_ipv6()
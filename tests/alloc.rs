@@ -0,0 +1,86 @@
+//! Allocations are counted process wide, so this file holds a single test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use maxminddb::{City, Enterprise, Reader};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[test]
+fn lookup_into_does_not_allocate() {
+    let ips = [
+        "81.2.69.142",
+        "2.125.160.216",
+        "89.160.20.128",
+        "216.160.83.56",
+        // without subdivisions
+        "2a02:ff80::",
+    ]
+    .into_iter()
+    .map(|ip| IpAddr::from_str(ip).unwrap())
+    .collect::<Vec<_>>();
+
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let mut city = City::default();
+    // warm up, the subdivisions buffer grows to its largest size
+    for ip in &ips {
+        reader.lookup_into(&mut city, *ip).unwrap();
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..10 {
+        for ip in &ips {
+            reader.lookup_into(&mut city, *ip).unwrap();
+        }
+    }
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+
+    // the record is fully replaced
+    reader.lookup_into(&mut city, ips[1]).unwrap();
+    assert_eq!(city.city_name(&["en"]), Some("Boxford"));
+    assert_eq!(city.subdivisions.as_ref().unwrap().len(), 2);
+    reader.lookup_into(&mut city, ips[0]).unwrap();
+    assert_eq!(city.city_name(&["en"]), Some("London"));
+    assert_eq!(city.subdivisions.as_ref().unwrap().len(), 1);
+    reader.lookup_into(&mut city, ips[4]).unwrap();
+    assert_eq!(city.city_name(&["en"]), None);
+    assert!(city.subdivisions.as_ref().unwrap().is_empty());
+
+    let reader = Reader::open_file("./testdata/GeoIP2-Enterprise-Test.mmdb").unwrap();
+    let mut enterprise = Enterprise::default();
+    let ip = IpAddr::from_str("74.209.24.0").unwrap();
+    // without subdivisions
+    let bhutan = IpAddr::from_str("67.43.156.0").unwrap();
+    reader.lookup_into(&mut enterprise, ip).unwrap();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..10 {
+        reader.lookup_into(&mut enterprise, ip).unwrap();
+        reader.lookup_into(&mut enterprise, bhutan).unwrap();
+    }
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+    assert!(enterprise.subdivisions.as_ref().unwrap().is_empty());
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    reader.lookup_json(ip, &mut std::io::sink()).unwrap();
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+}
@@ -0,0 +1,249 @@
+// Generated from the schema of GeoIP2-City.
+
+use maxminddb::{Data, Decoder, Error};
+
+#[derive(Debug, Default)]
+pub struct GeoIP2City<'a> {
+    pub city: Option<City<'a>>,
+    pub continent: Option<Continent<'a>>,
+    pub country: Option<Country<'a>>,
+    pub location: Option<Location<'a>>,
+    pub postal: Option<Postal<'a>>,
+    pub registered_country: Option<Country<'a>>,
+    pub represented_country: Option<RepresentedCountry<'a>>,
+    pub subdivisions: Option<Vec<Subdivision<'a>>>,
+    pub traits: Option<Traits>,
+}
+
+impl<'a> Decoder<'a> for GeoIP2City<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut geo_ip2_city = GeoIP2City::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "city" => geo_ip2_city.city = Some(<City<'a>>::decode(buf, offset)?),
+                "continent" => geo_ip2_city.continent = Some(<Continent<'a>>::decode(buf, offset)?),
+                "country" => geo_ip2_city.country = Some(<Country<'a>>::decode(buf, offset)?),
+                "location" => geo_ip2_city.location = Some(<Location<'a>>::decode(buf, offset)?),
+                "postal" => geo_ip2_city.postal = Some(<Postal<'a>>::decode(buf, offset)?),
+                "registered_country" => {
+                    geo_ip2_city.registered_country = Some(<Country<'a>>::decode(buf, offset)?)
+                }
+                "represented_country" => {
+                    geo_ip2_city.represented_country =
+                        Some(<RepresentedCountry<'a>>::decode(buf, offset)?)
+                }
+                "subdivisions" => {
+                    geo_ip2_city.subdivisions = Some(<Vec<Subdivision<'a>>>::decode(buf, offset)?)
+                }
+                "traits" => geo_ip2_city.traits = Some(Traits::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(geo_ip2_city)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Traits {
+    pub is_anonymous_proxy: Option<bool>,
+}
+
+impl<'a> Decoder<'a> for Traits {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut traits = Traits::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "is_anonymous_proxy" => {
+                    traits.is_anonymous_proxy = Some(bool::decode(buf, offset)?)
+                }
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(traits)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Subdivision<'a> {
+    pub geoname_id: Option<u32>,
+    pub iso_code: Option<&'a str>,
+    pub names: Option<maxminddb::models::Names<'a>>,
+}
+
+impl<'a> Decoder<'a> for Subdivision<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut subdivision = Subdivision::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "geoname_id" => subdivision.geoname_id = Some(u32::decode(buf, offset)?),
+                "iso_code" => subdivision.iso_code = Some(<&'a str>::decode(buf, offset)?),
+                "names" => {
+                    subdivision.names = Some(<maxminddb::models::Names<'a>>::decode(buf, offset)?)
+                }
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(subdivision)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RepresentedCountry<'a> {
+    pub geoname_id: Option<u32>,
+    pub iso_code: Option<&'a str>,
+    pub names: Option<maxminddb::models::Names<'a>>,
+    pub r#type: Option<&'a str>,
+}
+
+impl<'a> Decoder<'a> for RepresentedCountry<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut represented_country = RepresentedCountry::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "geoname_id" => represented_country.geoname_id = Some(u32::decode(buf, offset)?),
+                "iso_code" => represented_country.iso_code = Some(<&'a str>::decode(buf, offset)?),
+                "names" => {
+                    represented_country.names =
+                        Some(<maxminddb::models::Names<'a>>::decode(buf, offset)?)
+                }
+                "type" => represented_country.r#type = Some(<&'a str>::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(represented_country)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Postal<'a> {
+    pub code: Option<&'a str>,
+}
+
+impl<'a> Decoder<'a> for Postal<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut postal = Postal::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "code" => postal.code = Some(<&'a str>::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(postal)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Location<'a> {
+    pub accuracy_radius: Option<u16>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub metro_code: Option<u16>,
+    pub time_zone: Option<&'a str>,
+}
+
+impl<'a> Decoder<'a> for Location<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut location = Location::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "accuracy_radius" => location.accuracy_radius = Some(u16::decode(buf, offset)?),
+                "latitude" => location.latitude = Some(f64::decode(buf, offset)?),
+                "longitude" => location.longitude = Some(f64::decode(buf, offset)?),
+                "metro_code" => location.metro_code = Some(u16::decode(buf, offset)?),
+                "time_zone" => location.time_zone = Some(<&'a str>::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(location)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Country<'a> {
+    pub geoname_id: Option<u32>,
+    pub is_in_european_union: Option<bool>,
+    pub iso_code: Option<&'a str>,
+    pub names: Option<maxminddb::models::Names<'a>>,
+}
+
+impl<'a> Decoder<'a> for Country<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut country = Country::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "geoname_id" => country.geoname_id = Some(u32::decode(buf, offset)?),
+                "is_in_european_union" => {
+                    country.is_in_european_union = Some(bool::decode(buf, offset)?)
+                }
+                "iso_code" => country.iso_code = Some(<&'a str>::decode(buf, offset)?),
+                "names" => {
+                    country.names = Some(<maxminddb::models::Names<'a>>::decode(buf, offset)?)
+                }
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(country)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Continent<'a> {
+    pub code: Option<&'a str>,
+    pub geoname_id: Option<u32>,
+    pub names: Option<maxminddb::models::Names<'a>>,
+}
+
+impl<'a> Decoder<'a> for Continent<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut continent = Continent::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "code" => continent.code = Some(<&'a str>::decode(buf, offset)?),
+                "geoname_id" => continent.geoname_id = Some(u32::decode(buf, offset)?),
+                "names" => {
+                    continent.names = Some(<maxminddb::models::Names<'a>>::decode(buf, offset)?)
+                }
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(continent)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct City<'a> {
+    pub geoname_id: Option<u32>,
+    pub names: Option<maxminddb::models::Names<'a>>,
+}
+
+impl<'a> Decoder<'a> for City<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut city = City::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "geoname_id" => city.geoname_id = Some(u32::decode(buf, offset)?),
+                "names" => city.names = Some(<maxminddb::models::Names<'a>>::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(city)
+    }
+}
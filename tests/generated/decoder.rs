@@ -0,0 +1,89 @@
+// Generated from the schema of Synthetic-Decoder-Test.
+
+use maxminddb::{Data, Decoder, Error};
+
+#[derive(Debug, Default)]
+pub struct SyntheticDecoderTest<'a> {
+    pub array: Option<Vec<u32>>,
+    pub boolean: Option<bool>,
+    pub bytes: Option<&'a [u8]>,
+    pub double: Option<f64>,
+    pub float: Option<f32>,
+    pub int32: Option<i32>,
+    pub map: Option<Map<'a>>,
+    pub uint128: Option<u128>,
+    pub uint16: Option<u16>,
+    pub uint32: Option<u32>,
+    pub uint64: Option<u64>,
+    pub utf8_string: Option<&'a str>,
+}
+
+impl<'a> Decoder<'a> for SyntheticDecoderTest<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut synthetic_decoder_test = SyntheticDecoderTest::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "array" => synthetic_decoder_test.array = Some(<Vec<u32>>::decode(buf, offset)?),
+                "boolean" => synthetic_decoder_test.boolean = Some(bool::decode(buf, offset)?),
+                "bytes" => synthetic_decoder_test.bytes = Some(<&'a [u8]>::decode(buf, offset)?),
+                "double" => synthetic_decoder_test.double = Some(f64::decode(buf, offset)?),
+                "float" => synthetic_decoder_test.float = Some(f32::decode(buf, offset)?),
+                "int32" => synthetic_decoder_test.int32 = Some(i32::decode(buf, offset)?),
+                "map" => synthetic_decoder_test.map = Some(<Map<'a>>::decode(buf, offset)?),
+                "uint128" => synthetic_decoder_test.uint128 = Some(u128::decode(buf, offset)?),
+                "uint16" => synthetic_decoder_test.uint16 = Some(u16::decode(buf, offset)?),
+                "uint32" => synthetic_decoder_test.uint32 = Some(u32::decode(buf, offset)?),
+                "uint64" => synthetic_decoder_test.uint64 = Some(u64::decode(buf, offset)?),
+                "utf8_string" => {
+                    synthetic_decoder_test.utf8_string = Some(<&'a str>::decode(buf, offset)?)
+                }
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(synthetic_decoder_test)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Map<'a> {
+    pub map_x: Option<MapX<'a>>,
+}
+
+impl<'a> Decoder<'a> for Map<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut map = Map::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "mapX" => map.map_x = Some(<MapX<'a>>::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MapX<'a> {
+    pub array_x: Option<Vec<u32>>,
+    pub utf8_string_x: Option<&'a str>,
+}
+
+impl<'a> Decoder<'a> for MapX<'a> {
+    fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+        let mut map_x = MapX::default();
+
+        for _ in 0..size {
+            match <&str>::decode(buf, offset)? {
+                "arrayX" => map_x.array_x = Some(<Vec<u32>>::decode(buf, offset)?),
+                "utf8_stringX" => map_x.utf8_string_x = Some(<&'a str>::decode(buf, offset)?),
+                field => return Err(Error::UnknownField(field.to_string())),
+            }
+        }
+
+        Ok(map_x)
+    }
+}
@@ -1,6 +1,12 @@
+use std::collections::{BTreeMap, HashMap};
 use std::{net::IpAddr, str::FromStr};
 
-use maxminddb::{AnonymousIp, Asn, City, ConnectionType, Country, Domain, Enterprise, Isp, Reader};
+use maxminddb::models::{ConnectionKind, RepresentationKind, UserKind};
+use maxminddb::{
+    AnonymousIp, AnonymousPlus, Asn, AttributeValue, City, ConnectionType, Country, Data, Decoder,
+    DensityIncome, Domain, Enterprise, Error, FlattenOptions, IndexFormat, IpRisk, Isp,
+    JsonOptions, Reader, ReaderBuilder, StaticIpScore, UserCount, Value, Visitor, Walk,
+};
 
 #[test]
 fn anonymous_ip() {
@@ -66,6 +72,8 @@ fn enterprise() {
         assert_eq!(traits.domain, Some("frpt.net"));
         assert_eq!(traits.static_ip_score, Some(0.34));
         assert_eq!(traits.user_type, Some("residential"));
+        assert_eq!(traits.connection_kind(), Some(ConnectionKind::CableDsl));
+        assert_eq!(traits.user_kind(), Some(UserKind::Residential));
     }
     {
         let result = reader
@@ -79,6 +87,17 @@ fn enterprise() {
         assert_eq!(traits.domain, Some("in-addr.arpa"));
         assert_eq!(traits.static_ip_score, Some(0.34));
         assert_eq!(traits.user_type, Some("government"));
+        assert_eq!(traits.connection_kind(), Some(ConnectionKind::Corporate));
+        assert_eq!(traits.user_kind(), Some(UserKind::Government));
+    }
+    {
+        let result = reader
+            .lookup::<Enterprise>(IpAddr::from_str("202.196.224.0").unwrap())
+            .unwrap();
+
+        let represented_country = result.represented_country.unwrap();
+        assert_eq!(represented_country.iso_code, Some("US"));
+        assert_eq!(represented_country.country_type, Some("military"));
     }
 }
 
@@ -164,6 +183,20 @@ fn connection_type() {
             .unwrap();
         assert_eq!(result, "Cable/DSL");
     }
+    {
+        let result = reader
+            .lookup::<ConnectionType>(IpAddr::from_str("1.0.0.0").unwrap())
+            .unwrap();
+        assert_eq!(result.connection_kind(), Some(ConnectionKind::Dialup));
+        assert_eq!(result.connection_kind().unwrap().to_string(), "Dialup");
+    }
+
+    assert_eq!(
+        ConnectionKind::from("Fiber"),
+        ConnectionKind::Other("Fiber")
+    );
+    assert_eq!(ConnectionKind::from("Fiber").as_str(), "Fiber");
+    assert_eq!(UserKind::from("military"), UserKind::Military);
 }
 
 #[test]
@@ -345,3 +378,1119 @@ fn metadata() {
     assert_eq!(metadata.languages, vec!["en"]);
     assert_eq!(metadata.build_epoch, 1609263880);
 }
+
+#[test]
+fn decoder() {
+    let buf = std::fs::read("./testdata/Synthetic-Decoder-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    {
+        let result = reader
+            .lookup::<Value>(IpAddr::from_str("1.1.1.1").unwrap())
+            .unwrap();
+        assert_eq!(
+            result.get("array"),
+            Some(&Value::Array(vec![
+                Value::Uint32(1),
+                Value::Uint32(2),
+                Value::Uint32(3)
+            ]))
+        );
+        assert_eq!(result.get("boolean"), Some(&Value::Bool(true)));
+        assert_eq!(result.get("bytes"), Some(&Value::Bytes(&[0, 0, 0, 42])));
+        assert_eq!(result.get("double"), Some(&Value::Double(42.123456)));
+        assert_eq!(result.get("float"), Some(&Value::Float(1.1)));
+        assert_eq!(result.get("int32"), Some(&Value::Int32(-268435456)));
+        assert_eq!(
+            result.get("map").and_then(|v| v.get("mapX")),
+            Some(&Value::Map(vec![
+                (
+                    "arrayX",
+                    Value::Array(vec![Value::Uint32(7), Value::Uint32(8), Value::Uint32(9)])
+                ),
+                ("utf8_stringX", Value::String("hello")),
+            ]))
+        );
+        assert_eq!(
+            result.get("uint128"),
+            Some(&Value::Uint128(1329227995784915872903807060280344576))
+        );
+        assert_eq!(result.get("uint16"), Some(&Value::Uint16(100)));
+        assert_eq!(result.get("uint32"), Some(&Value::Uint32(268435456)));
+        assert_eq!(
+            result.get("uint64"),
+            Some(&Value::Uint64(1152921504606846976))
+        );
+        assert_eq!(
+            result.get("utf8_string"),
+            Some(&Value::String("unicode! ☯ - ♫"))
+        );
+    }
+    {
+        let result = reader
+            .lookup::<Value>(IpAddr::from_str("::").unwrap())
+            .unwrap();
+        assert_eq!(result.get("array"), Some(&Value::Array(vec![])));
+        assert_eq!(result.get("boolean"), Some(&Value::Bool(false)));
+        assert_eq!(result.get("bytes"), Some(&Value::Bytes(&[])));
+        assert_eq!(result.get("int32"), Some(&Value::Int32(0)));
+        assert_eq!(result.get("uint128"), Some(&Value::Uint128(0)));
+        assert_eq!(result.get("utf8_string"), Some(&Value::String("")));
+    }
+    {
+        let result = reader
+            .lookup::<Value>(IpAddr::from_str("255.255.255.255").unwrap())
+            .unwrap();
+        assert_eq!(result.get("double"), Some(&Value::Double(f64::INFINITY)));
+        assert_eq!(result.get("float"), Some(&Value::Float(f32::INFINITY)));
+        assert_eq!(result.get("int32"), Some(&Value::Int32(i32::MAX)));
+        assert_eq!(result.get("uint16"), Some(&Value::Uint16(u16::MAX)));
+        assert_eq!(result.get("uint32"), Some(&Value::Uint32(u32::MAX)));
+        assert_eq!(result.get("uint64"), Some(&Value::Uint64(u64::MAX)));
+        assert_eq!(result.get("uint128"), Some(&Value::Uint128(u128::MAX)));
+    }
+}
+
+#[test]
+fn localized_names() {
+    let mut reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    assert_eq!(reader.languages(), ["en"]);
+
+    let result = reader
+        .lookup::<City>(IpAddr::from_str("2.125.160.216").unwrap())
+        .unwrap();
+    let names = result.city.as_ref().unwrap().names.as_ref().unwrap();
+    assert_eq!(names.get("en"), Some("Boxford"));
+    assert_eq!(names.get("xx"), None);
+    assert_eq!(names.best(&["xx", "en"]), Some("Boxford"));
+    assert_eq!(
+        result.display_name(reader.languages()).as_deref(),
+        Some("Boxford, West Berkshire, United Kingdom")
+    );
+    assert_eq!(
+        result.display_name(&["zh-CN", "en"]).as_deref(),
+        Some("Boxford, 西伯克郡, 英国")
+    );
+    assert_eq!(result.subdivision_name(&["xx"]), None);
+    assert_eq!(result.display_name(&["xx"]), None);
+
+    assert!(matches!(
+        reader.set_languages(&["zh-CN", "xx"]),
+        Err(maxminddb::Error::UnknownLanguage(language)) if language == "xx"
+    ));
+    assert!(reader.set_languages(&["ja"]).is_err());
+    // regional variants of the metadata languages are fine
+    reader.set_languages(&["zh-CN", "en"]).unwrap();
+
+    let result = reader
+        .lookup::<City>(IpAddr::from_str("2.125.160.216").unwrap())
+        .unwrap();
+    assert_eq!(result.country_name(reader.languages()), Some("英国"));
+
+    // no chinese name, falls back to english
+    let result = reader
+        .lookup::<City>(IpAddr::from_str("81.2.69.142").unwrap())
+        .unwrap();
+    assert_eq!(result.city_name(reader.languages()), Some("London"));
+}
+
+#[test]
+fn represented_country() {
+    let reader = Reader::open_file("./testdata/GeoIP2-Country-Test.mmdb").unwrap();
+    let result = reader
+        .lookup::<Country>(IpAddr::from_str("202.196.224.0").unwrap())
+        .unwrap();
+
+    let represented_country = result.represented_country.unwrap();
+    assert_eq!(represented_country.representation_type, Some("military"));
+    assert_eq!(
+        represented_country.representation_kind(),
+        Some(RepresentationKind::Military)
+    );
+}
+
+#[test]
+fn anonymous_plus() {
+    let buf = std::fs::read("./testdata/Synthetic-Anonymous-Plus-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    {
+        let result = reader
+            .lookup::<AnonymousPlus>(IpAddr::from_str("1.2.0.1").unwrap())
+            .unwrap();
+        assert_eq!(result.anonymizer_confidence, Some(30));
+        assert_eq!(result.is_anonymous, Some(true));
+        assert_eq!(result.is_anonymous_vpn, Some(true));
+        assert_eq!(result.is_hosting_provider, None);
+        assert_eq!(result.network_last_seen, Some("2025-04-14"));
+        assert_eq!(result.provider_name, Some("foo"));
+    }
+    {
+        let result = reader
+            .lookup::<AnonymousPlus>(IpAddr::from_str("81.2.69.142").unwrap())
+            .unwrap();
+        assert_eq!(result.anonymizer_confidence, Some(99));
+        assert_eq!(result.is_public_proxy, Some(true));
+        assert_eq!(result.is_tor_exit_node, Some(true));
+        assert_eq!(result.network_last_seen, None);
+        assert_eq!(result.provider_name, None);
+    }
+}
+
+#[test]
+fn ip_risk() {
+    let buf = std::fs::read("./testdata/Synthetic-IP-Risk-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    {
+        let result = reader
+            .lookup::<IpRisk>(IpAddr::from_str("6.1.2.1").unwrap())
+            .unwrap();
+        assert_eq!(result.ip_risk, Some(0.01));
+        assert_eq!(result.is_anonymous, None);
+    }
+    {
+        let result = reader
+            .lookup::<IpRisk>(IpAddr::from_str("214.2.3.5").unwrap())
+            .unwrap();
+        assert_eq!(result.ip_risk, Some(25.0));
+        assert_eq!(result.is_anonymous, Some(true));
+        assert_eq!(result.is_anonymous_vpn, Some(true));
+        assert_eq!(result.is_hosting_provider, Some(true));
+        assert_eq!(result.is_tor_exit_node, None);
+    }
+}
+
+#[test]
+fn static_ip_score() {
+    let buf = std::fs::read("./testdata/Synthetic-Static-IP-Score-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    {
+        let result = reader
+            .lookup::<StaticIpScore>(IpAddr::from_str("1.2.3.4").unwrap())
+            .unwrap();
+        assert_eq!(result.score, Some(0.34));
+    }
+    {
+        let result = reader
+            .lookup::<StaticIpScore>(IpAddr::from_str("2a02:ff80::1").unwrap())
+            .unwrap();
+        assert_eq!(result.score, Some(99.0));
+    }
+}
+
+#[test]
+fn user_count() {
+    let buf = std::fs::read("./testdata/Synthetic-User-Count-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    {
+        let result = reader
+            .lookup::<UserCount>(IpAddr::from_str("1.2.3.4").unwrap())
+            .unwrap();
+        assert_eq!(result.ipv4_24, Some(4));
+        assert_eq!(result.ipv4_32, Some(3));
+        assert_eq!(result.ipv6_32, None);
+    }
+    {
+        let result = reader
+            .lookup::<UserCount>(IpAddr::from_str("2a02:ff80::1").unwrap())
+            .unwrap();
+        assert_eq!(result.ipv4_24, None);
+        assert_eq!(result.ipv6_32, Some(110));
+        assert_eq!(result.ipv6_48, Some(12));
+        assert_eq!(result.ipv6_64, Some(7));
+    }
+}
+
+#[test]
+fn density_income() {
+    let buf = std::fs::read("./testdata/Synthetic-DensityIncome-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    {
+        let result = reader
+            .lookup::<DensityIncome>(IpAddr::from_str("5.83.124.123").unwrap())
+            .unwrap();
+        assert_eq!(result.average_income, Some(32323));
+        assert_eq!(result.population_density, Some(1232));
+    }
+    {
+        let result = reader.lookup::<DensityIncome>(IpAddr::from_str("1.1.1.1").unwrap());
+        assert!(matches!(result, Err(maxminddb::Error::AddressNotFound)));
+    }
+}
+
+#[test]
+fn city_plus() {
+    let buf = std::fs::read("./testdata/Synthetic-City-Plus-Test.mmdb").unwrap();
+    let reader = Reader::from_bytes(buf).unwrap();
+    let ip = IpAddr::from_str("81.2.69.142").unwrap();
+    {
+        let result = reader.lookup::<City>(ip).unwrap();
+
+        let city = result.city.as_ref().unwrap();
+        assert_eq!(city.confidence, Some(60));
+        assert_eq!(result.city_name(&["zh-CN"]), Some("伦敦"));
+
+        let country = result.country.as_ref().unwrap();
+        assert_eq!(country.confidence, Some(99));
+        assert_eq!(country.iso_code, Some("GB"));
+
+        let location = result.location.as_ref().unwrap();
+        assert_eq!(location.average_income, Some(45000));
+        assert_eq!(location.population_density, Some(5374));
+        assert_eq!(location.metro_code, Some(0));
+
+        let postal = result.postal.as_ref().unwrap();
+        assert_eq!(postal.code, Some("EC2V"));
+        assert_eq!(postal.confidence, Some(40));
+
+        let subdivisions = result.subdivisions.as_ref().unwrap();
+        assert_eq!(subdivisions[0].confidence, Some(80));
+
+        let represented_country = result.represented_country.as_ref().unwrap();
+        assert_eq!(
+            represented_country.representation_kind(),
+            Some(RepresentationKind::Military)
+        );
+
+        let traits = result.traits.as_ref().unwrap();
+        assert_eq!(traits.autonomous_system_number, Some(20712));
+        assert_eq!(
+            traits.autonomous_system_organization,
+            Some("Andrews & Arnold Ltd")
+        );
+        assert_eq!(traits.connection_kind(), Some(ConnectionKind::Corporate));
+        assert_eq!(traits.domain, Some("in-addr.arpa"));
+        assert_eq!(traits.ip_address, Some("81.2.69.142"));
+        assert_eq!(traits.is_anonymous, Some(true));
+        assert_eq!(traits.is_anonymous_proxy, Some(false));
+        assert_eq!(traits.is_anonymous_vpn, Some(true));
+        assert_eq!(traits.is_anycast, Some(true));
+        assert_eq!(traits.is_hosting_provider, Some(true));
+        assert_eq!(traits.is_legitimate_proxy, Some(true));
+        assert_eq!(traits.is_public_proxy, Some(true));
+        assert_eq!(traits.is_residential_proxy, Some(true));
+        assert_eq!(traits.is_satellite_provider, Some(false));
+        assert_eq!(traits.is_tor_exit_node, Some(true));
+        assert_eq!(traits.isp, Some("Andrews & Arnold Ltd"));
+        assert_eq!(traits.mobile_country_code, Some("234"));
+        assert_eq!(traits.mobile_network_code, Some("10"));
+        assert_eq!(traits.network, Some("81.2.69.0/24"));
+        assert_eq!(traits.organization, Some("STONEHOUSE office network"));
+        assert_eq!(traits.static_ip_score, Some(0.34));
+        assert_eq!(traits.user_count, Some(2));
+        assert_eq!(traits.user_kind(), Some(UserKind::Government));
+    }
+    {
+        let result = reader.lookup::<Enterprise>(ip).unwrap();
+
+        assert_eq!(result.city.unwrap().confidence, Some(60));
+        assert_eq!(result.postal.unwrap().confidence, Some(40));
+        assert_eq!(
+            result.represented_country.unwrap().representation_kind(),
+            Some(RepresentationKind::Military)
+        );
+
+        let traits = result.traits.unwrap();
+        assert_eq!(traits.ip_address, Some("81.2.69.142"));
+        assert_eq!(traits.network, Some("81.2.69.0/24"));
+        assert_eq!(traits.user_count, Some(2));
+    }
+}
+
+#[test]
+fn verify() {
+    for entry in std::fs::read_dir("./testdata").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "mmdb") {
+            let mut reader = Reader::open_file(&path).unwrap();
+            assert!(!reader.is_verified());
+            reader.verify().unwrap();
+            assert!(reader.is_verified());
+        }
+    }
+
+    let mut reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    reader.verify().unwrap();
+    let city = reader
+        .lookup::<City>(IpAddr::from_str("81.2.69.142").unwrap())
+        .unwrap();
+    assert_eq!(city.city_name(reader.languages()), Some("London"));
+
+    // a name that isn't UTF-8 anymore
+    let mut buf = std::fs::read("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let at = buf.windows(6).position(|w| w == b"London").unwrap();
+    buf[at] = 0xff;
+    let mut reader = Reader::from_bytes(buf).unwrap();
+    assert!(matches!(reader.verify(), Err(Error::InvalidUtf8(_))));
+    assert!(!reader.is_verified());
+
+    // verified readers read the same strings
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let mut verified = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    verified.verify().unwrap();
+    for ip in sample_ips() {
+        assert_eq!(
+            format!("{:?}", verified.lookup::<City>(ip).ok()),
+            format!("{:?}", reader.lookup::<City>(ip).ok())
+        );
+        assert_eq!(
+            verified.lookup::<Value>(ip).ok(),
+            reader.lookup::<Value>(ip).ok()
+        );
+        let (mut json, mut verified_json) = (Vec::new(), Vec::new());
+        assert_eq!(
+            verified.lookup_json(ip, &mut verified_json).is_ok(),
+            reader.lookup_json(ip, &mut json).is_ok()
+        );
+        assert_eq!(verified_json, json);
+    }
+}
+
+// with unsafe-str, strings are never validated
+#[cfg(not(feature = "unsafe-str"))]
+#[test]
+fn verified_strings_at_other_offsets() {
+    // a decoder reading a string in the middle of a verified value
+    struct Inside<'a>(Result<&'a str, Error>);
+    impl<'a> Decoder<'a> for Inside<'a> {
+        fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+            for _ in 0..size * 2 {
+                Value::decode(buf, offset)?;
+            }
+            Ok(Inside(<&str>::decode(buf, &mut 4)))
+        }
+    }
+
+    // {"a": bytes 41 ff}, the bytes look like a string that isn't UTF-8
+    let mut reader = one_record_database(&[0xe1, 0x41, b'a', 0x82, 0x41, 0xff]);
+    reader.verify().unwrap();
+    let ip = IpAddr::from_str("1.1.1.1").unwrap();
+    let inside = reader.lookup::<Inside>(ip).unwrap();
+    assert!(matches!(inside.0, Err(Error::InvalidUtf8(_))));
+}
+
+/// Addresses spread over the whole address space, and a few in the test
+/// databases.
+fn sample_ips() -> Vec<IpAddr> {
+    let mut ips = vec![
+        IpAddr::from_str("81.2.69.142").unwrap(),
+        IpAddr::from_str("2.125.160.216").unwrap(),
+        IpAddr::from_str("::81.2.69.142").unwrap(),
+        IpAddr::from_str("2001:218::").unwrap(),
+        IpAddr::from_str("2a02:cf40::").unwrap(),
+    ];
+    ips.extend((0..4096u32).map(|i| IpAddr::from(i.wrapping_mul(0x9e37_79b9).to_be_bytes())));
+    ips.extend((0..4096u128).map(|i| {
+        IpAddr::from(std::net::Ipv6Addr::from(
+            i.wrapping_mul(0x9e37_79b9_7f4a_7c15) << 64,
+        ))
+    }));
+    ips
+}
+
+/// An IPv4 database of one node whose record is a map with a pointer back
+/// to itself, `{"a": <the map>}`.
+fn cyclic_database() -> Reader<Vec<u8>> {
+    one_record_database(&[0xe1, 0x41, b'a', 0x20, 0x00])
+}
+
+/// An IPv4 database of one node, every address has the record at the
+/// start of `data`.
+fn one_record_database(data: &[u8]) -> Reader<Vec<u8>> {
+    let mut buf = vec![0x00, 0x00, 0x11, 0x00, 0x00, 0x11];
+    buf.extend_from_slice(&[0; 16]);
+    buf.extend_from_slice(data);
+    buf.extend_from_slice(b"\xab\xcd\xefMaxMind.com");
+    buf.extend_from_slice(b"\xe3\x4anode_count\xc1\x01");
+    buf.extend_from_slice(b"\x4brecord_size\xa1\x18");
+    buf.extend_from_slice(b"\x4aip_version\xa1\x04");
+    Reader::from_bytes(buf).unwrap()
+}
+
+#[test]
+fn cyclic_walk() {
+    struct Nothing;
+    impl Visitor<'_> for Nothing {}
+
+    let reader = cyclic_database();
+    let ip = IpAddr::from_str("1.1.1.1").unwrap();
+    assert!(matches!(
+        reader.lookup_visit(ip, &mut Nothing),
+        Err(Error::InvalidOffset)
+    ));
+    assert!(matches!(
+        reader.lookup_json(ip, &mut Vec::new()),
+        Err(Error::InvalidOffset)
+    ));
+    assert!(matches!(
+        reader
+            .lookup_flatten(ip, &FlattenOptions::default())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>(),
+        Err(Error::InvalidOffset)
+    ));
+    assert!(matches!(reader.infer_schema(), Err(Error::InvalidOffset)));
+}
+
+#[test]
+fn cyclic_value() {
+    let reader = cyclic_database();
+    let ip = IpAddr::from_str("1.1.1.1").unwrap();
+    assert!(matches!(
+        reader.lookup::<Value>(ip),
+        Err(Error::InvalidOffset)
+    ));
+    assert!(matches!(
+        reader.lookup::<HashMap<&str, Value>>(ip),
+        Err(Error::InvalidOffset)
+    ));
+}
+
+const TREE_TEST_DATABASES: [&str; 3] = [
+    "./testdata/GeoIP2-City-Test.mmdb",
+    "./testdata/GeoLite2-ASN-Test.mmdb",
+    "./testdata/Synthetic-Decoder-Test.mmdb",
+];
+
+#[test]
+fn jump_table() {
+    let ips = sample_ips();
+    for path in TREE_TEST_DATABASES {
+        let reader = Reader::open_file(path).unwrap();
+        for bits in [1, 8, 16, 24] {
+            let jump = ReaderBuilder::new()
+                .jump_table(bits)
+                .open_file(path)
+                .unwrap();
+            for ip in &ips {
+                assert_eq!(
+                    reader.lookup::<Value>(*ip).ok(),
+                    jump.lookup::<Value>(*ip).ok(),
+                    "{path} {bits} bits {ip}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn blocked_tree() {
+    let ips = sample_ips();
+    for path in TREE_TEST_DATABASES {
+        let reader = Reader::open_file(path).unwrap();
+        let blocked = ReaderBuilder::new()
+            .blocked_tree(true)
+            .open_file(path)
+            .unwrap();
+        let both = ReaderBuilder::new()
+            .blocked_tree(true)
+            .jump_table(8)
+            .open_file(path)
+            .unwrap();
+        for ip in &ips {
+            let expected = reader.lookup::<Value>(*ip).ok();
+            assert_eq!(expected, blocked.lookup::<Value>(*ip).ok(), "{path} {ip}");
+            assert_eq!(expected, both.lookup::<Value>(*ip).ok(), "{path} {ip}");
+        }
+    }
+}
+
+#[test]
+fn ipv4_ranges() {
+    let ips = sample_ips();
+    for path in TREE_TEST_DATABASES {
+        let reader = Reader::open_file(path).unwrap();
+        let ranges = ReaderBuilder::new()
+            .ipv4_ranges(true)
+            .open_file(path)
+            .unwrap();
+        for ip in &ips {
+            assert_eq!(
+                reader.lookup::<Value>(*ip).ok(),
+                ranges.lookup::<Value>(*ip).ok(),
+                "{path} {ip}"
+            );
+        }
+    }
+}
+
+#[test]
+fn json() {
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let lookup = |ip: &str, options: &JsonOptions| {
+        let mut out = Vec::new();
+        reader
+            .lookup_json_with(IpAddr::from_str(ip).unwrap(), options, &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let json = lookup("81.2.69.142", &JsonOptions::default());
+    assert!(json.starts_with(r#"{"city":{"geoname_id":2643743,"names":{"de":"London","#));
+    assert!(json.ends_with(r#""traits":{"ip_address":"81.2.69.142","network":"81.2.69.142/31"}}"#));
+
+    let fields = [
+        "city.names.en",
+        "country.iso_code",
+        "subdivisions.iso_code",
+        "traits.network",
+    ];
+    let options = JsonOptions {
+        fields: Some(&fields),
+        ..JsonOptions::default()
+    };
+    assert_eq!(
+        lookup("2.125.160.216", &options),
+        r#"{"city":{"names":{"en":"Boxford"}},"country":{"iso_code":"GB"},"subdivisions":[{"iso_code":"ENG"},{"iso_code":"WBK"}],"traits":{"network":"2.125.160.216/29"}}"#
+    );
+    // the record has no traits
+    assert_eq!(
+        lookup("2001:218::", &options),
+        r#"{"country":{"iso_code":"JP"},"traits":{"network":"2001:218::/32"}}"#
+    );
+
+    let options = JsonOptions {
+        fields: None,
+        web_service: false,
+    };
+    let reader = Reader::open_file("./testdata/Synthetic-Decoder-Test.mmdb").unwrap();
+    let mut out = Vec::new();
+    reader
+        .lookup_json_with(IpAddr::from_str("1.1.1.1").unwrap(), &options, &mut out)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"{"array":[1,2,3],"boolean":true,"bytes":[0,0,0,42],"double":42.123456,"float":1.100000023841858,"int32":-268435456,"map":{"mapX":{"arrayX":[7,8,9],"utf8_stringX":"hello"}},"uint128":1329227995784915872903807060280344576,"uint16":100,"uint32":268435456,"uint64":1152921504606846976,"utf8_string":"unicode! ☯ - ♫"}"#
+    );
+}
+
+#[test]
+fn flatten() {
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let city = reader
+        .lookup::<Value>(IpAddr::from_str("2.125.160.216").unwrap())
+        .unwrap();
+    let flat = |options: &FlattenOptions| {
+        city.flatten(options)
+            .map(|(path, value)| format!("{path}={value:?}"))
+            .collect::<Vec<_>>()
+    };
+
+    let all = flat(&FlattenOptions::default());
+    assert!(all.contains(&"city.names.en=String(\"Boxford\")".to_string()));
+    assert!(all.contains(&"subdivisions.1.iso_code=String(\"WBK\")".to_string()));
+    assert!(all.contains(&"location.latitude=Double(51.75)".to_string()));
+
+    let languages = ["xx", "en"];
+    let options = FlattenOptions {
+        prefix: "geo",
+        index_format: IndexFormat::Brackets,
+        languages: Some(&languages),
+        include: &["city.*", "subdivisions*"],
+        exclude: &["*.geoname_id"],
+    };
+    assert_eq!(
+        flat(&options),
+        [
+            "geo.city.name=String(\"Boxford\")",
+            "geo.subdivisions[0].iso_code=String(\"ENG\")",
+            "geo.subdivisions[0].name=String(\"England\")",
+            "geo.subdivisions[1].iso_code=String(\"WBK\")",
+            "geo.subdivisions[1].name=String(\"West Berkshire\")",
+        ]
+    );
+
+    let scalar = Value::Uint32(7);
+    let options = FlattenOptions {
+        prefix: "asn",
+        ..FlattenOptions::default()
+    };
+    assert_eq!(
+        scalar.flatten(&options).collect::<Vec<_>>(),
+        [("asn".to_string(), &scalar)]
+    );
+}
+
+#[test]
+fn semantic_conventions() {
+    fn string(s: &str) -> AttributeValue<'_> {
+        AttributeValue::String(s.into())
+    }
+
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let city = reader
+        .lookup::<City>(IpAddr::from_str("2.125.160.216").unwrap())
+        .unwrap();
+    assert_eq!(
+        city.otel_attributes(&["en"]).collect::<Vec<_>>(),
+        [
+            ("geo.continent.code", string("EU")),
+            ("geo.country.iso_code", string("GB")),
+            ("geo.region.iso_code", string("GB-WBK")),
+            ("geo.locality.name", string("Boxford")),
+            ("geo.postal_code", string("OX1")),
+            ("geo.location.lat", AttributeValue::Double(51.75)),
+            ("geo.location.lon", AttributeValue::Double(-1.25)),
+        ]
+    );
+    let fields = city.ecs_fields(&["de", "en"]).collect::<Vec<_>>();
+    assert!(fields.contains(&("source.geo.country_name", string("Vereinigtes Königreich"))));
+    assert!(fields.contains(&("source.geo.region_name", string("West Berkshire"))));
+    assert!(fields.contains(&(
+        "source.geo.location",
+        AttributeValue::GeoPoint {
+            lat: 51.75,
+            lon: -1.25
+        }
+    )));
+
+    let reader = Reader::open_file("./testdata/GeoIP2-Enterprise-Test.mmdb").unwrap();
+    let enterprise = reader
+        .lookup::<Enterprise>(IpAddr::from_str("74.209.24.0").unwrap())
+        .unwrap();
+    let fields = enterprise.ecs_fields(&["en"]).collect::<Vec<_>>();
+    assert_eq!(fields.len(), 12);
+    assert_eq!(fields[2], ("source.geo.country_iso_code", string("US")));
+    assert_eq!(fields[10], ("source.as.number", AttributeValue::Int(14671)));
+
+    let reader = Reader::open_file("./testdata/GeoLite2-ASN-Test.mmdb").unwrap();
+    let asn = reader
+        .lookup::<Asn>(IpAddr::from_str("1.128.0.0").unwrap())
+        .unwrap();
+    assert_eq!(
+        asn.ecs_fields().collect::<Vec<_>>(),
+        [
+            ("source.as.number", AttributeValue::Int(1221)),
+            ("source.as.organization.name", string("Telstra Pty Ltd")),
+        ]
+    );
+
+    let reader = Reader::open_file("./testdata/GeoIP2-ISP-Test.mmdb").unwrap();
+    let isp = reader
+        .lookup::<Isp>(IpAddr::from_str("1.128.0.0").unwrap())
+        .unwrap();
+    assert_eq!(isp.otel_attributes().count(), 0);
+    assert_eq!(isp.ecs_fields().count(), 2);
+    let mobile = Isp {
+        isp: Some("Vodafone"),
+        mobile_country_code: Some("234"),
+        mobile_network_code: Some("15"),
+        ..Isp::default()
+    };
+    assert_eq!(
+        mobile.otel_attributes().collect::<Vec<_>>(),
+        [
+            ("network.carrier.mcc", string("234")),
+            ("network.carrier.mnc", string("15")),
+            ("network.carrier.name", string("Vodafone")),
+        ]
+    );
+}
+
+#[test]
+fn std_decoders() {
+    #[derive(Default)]
+    struct Record<'a> {
+        array: Vec<u8>,
+        boolean: bool,
+        bytes: &'a [u8],
+        double: f64,
+        float: f32,
+        int32: i64,
+        map: BTreeMap<&'a str, HashMap<&'a str, Value<'a>>>,
+        uint128: u128,
+        uint16: Option<u16>,
+        utf8_string: &'a str,
+        triple: Option<(u32, u64, i8)>,
+    }
+
+    impl<'a> Decoder<'a> for Record<'a> {
+        fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+            let mut record = Record::default();
+            for _ in 0..size {
+                match <&str>::decode(buf, offset)? {
+                    "array" => {
+                        let mut copy = *offset;
+                        record.array = Vec::decode(buf, offset)?;
+                        record.triple = Option::decode(buf, &mut copy)?;
+                    }
+                    "boolean" => record.boolean = bool::decode(buf, offset)?,
+                    "bytes" => record.bytes = <&[u8]>::decode(buf, offset)?,
+                    "double" => record.double = f64::decode(buf, offset)?,
+                    "float" => record.float = f32::decode(buf, offset)?,
+                    "int32" => record.int32 = i64::decode(buf, offset)?,
+                    "map" => record.map = BTreeMap::decode(buf, offset)?,
+                    "uint128" => record.uint128 = u128::decode(buf, offset)?,
+                    "uint16" => record.uint16 = Option::decode(buf, offset)?,
+                    "utf8_string" => record.utf8_string = <&str>::decode(buf, offset)?,
+                    _ => {
+                        Value::decode(buf, offset)?;
+                    }
+                }
+            }
+
+            Ok(record)
+        }
+    }
+
+    let reader = Reader::open_file("./testdata/Synthetic-Decoder-Test.mmdb").unwrap();
+    let ip = IpAddr::from_str("1.1.1.1").unwrap();
+    let record = reader.lookup::<Record>(ip).unwrap();
+    assert_eq!(record.array, [1, 2, 3]);
+    assert!(record.boolean);
+    assert_eq!(record.bytes, [0, 0, 0, 42]);
+    assert_eq!(record.double, 42.123456);
+    assert_eq!(record.float, 1.1);
+    assert_eq!(record.int32, -268435456);
+    assert_eq!(record.map["mapX"]["utf8_stringX"], Value::String("hello"));
+    assert_eq!(record.uint128, 1 << 120);
+    assert_eq!(record.uint16, Some(100));
+    assert_eq!(record.utf8_string, "unicode! ☯ - ♫");
+    assert_eq!(record.triple, Some((1, 2, 3)));
+
+    let map = reader.lookup::<HashMap<&str, Value>>(ip).unwrap();
+    assert_eq!(map.len(), 12);
+    assert_eq!(map["uint32"], Value::Uint32(1 << 28));
+
+    // the wrong types
+    #[derive(Debug)]
+    struct Pair(Option<(u8, u8)>);
+    impl<'a> Decoder<'a> for Pair {
+        fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+            let mut pair = Pair(None);
+            for _ in 0..size {
+                match <&str>::decode(buf, offset)? {
+                    "array" => pair.0 = Option::decode(buf, offset)?,
+                    _ => {
+                        Value::decode(buf, offset)?;
+                    }
+                }
+            }
+            Ok(pair)
+        }
+    }
+    assert!(matches!(
+        reader.lookup::<Pair>(ip),
+        Err(Error::InvalidArrayLength(3))
+    ));
+    assert!(matches!(
+        reader.lookup::<Vec<u32>>(ip),
+        Err(Error::InvalidDataType(_))
+    ));
+}
+
+#[test]
+fn decoders_of_other_buffers() {
+    // "code" at 0, then a map pointing to it as its key
+    static POSTAL: [u8; 11] = [
+        0x44, b'c', b'o', b'd', b'e', 0xe1, 0x20, 0x00, 0x42, b'A', b'B',
+    ];
+    // with unsafe-str, strings are never validated
+    #[cfg(not(feature = "unsafe-str"))]
+    static INVALID: [u8; 2] = [0x41, 0xff];
+
+    // a record decoding buffers of its own while the reader decodes it
+    struct Record<'a> {
+        city: City<'a>,
+        postal: Result<maxminddb::models::Postal<'static>, Error>,
+        #[cfg(not(feature = "unsafe-str"))]
+        string: Result<&'static str, Error>,
+    }
+
+    impl<'a> Decoder<'a> for Record<'a> {
+        fn decode_with_size(buf: Data<'a>, offset: &mut usize, size: usize) -> Result<Self, Error> {
+            Ok(Record {
+                postal: Decoder::decode(Data::new(&POSTAL), &mut 5),
+                #[cfg(not(feature = "unsafe-str"))]
+                string: <&str>::decode(Data::new(&INVALID), &mut 0),
+                city: City::decode_with_size(buf, offset, size)?,
+            })
+        }
+    }
+
+    let mut reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    reader.verify().unwrap();
+    let ip = IpAddr::from_str("81.2.69.142").unwrap();
+    for _ in 0..2 {
+        let record = reader.lookup::<Record>(ip).unwrap();
+        assert_eq!(record.city.city_name(reader.languages()), Some("London"));
+        assert_eq!(record.postal.unwrap().code, Some("AB"));
+        #[cfg(not(feature = "unsafe-str"))]
+        assert!(matches!(record.string, Err(Error::InvalidUtf8(_))));
+    }
+}
+
+#[test]
+fn scalar_records() {
+    let mut reader = Reader::open_file("./testdata/Synthetic-Scalars-Test.mmdb").unwrap();
+    reader.verify().unwrap();
+    let ip = |ip: &str| IpAddr::from_str(ip).unwrap();
+
+    assert_eq!(reader.lookup::<u32>(ip("1.0.0.1")).unwrap(), 13335);
+    assert_eq!(reader.lookup::<&str>(ip("2.0.0.1")).unwrap(), "US");
+    assert_eq!(
+        reader.lookup::<Vec<&str>>(ip("3.0.0.1")).unwrap(),
+        ["US", "CA"]
+    );
+    assert!(reader.lookup::<bool>(ip("4.0.0.1")).unwrap());
+    assert_eq!(
+        reader.lookup::<Value>(ip("1.0.0.1")).unwrap(),
+        Value::Uint32(13335)
+    );
+
+    // map decoders still want a map
+    assert!(matches!(
+        reader.lookup::<Asn>(ip("1.0.0.1")),
+        Err(Error::InvalidDataType(_))
+    ));
+    assert!(matches!(
+        reader.lookup::<u32>(ip("5.0.0.1")),
+        Err(Error::InvalidDataType(_))
+    ));
+    let asn = reader.lookup::<Asn>(ip("5.0.0.1")).unwrap();
+    assert_eq!(asn.autonomous_system_number, Some(13335));
+
+    let mut codes: Vec<&str> = Vec::new();
+    reader.lookup_into(&mut codes, ip("3.0.0.1")).unwrap();
+    assert_eq!(codes, ["US", "CA"]);
+
+    let mut out = Vec::new();
+    reader.lookup_json(ip("3.0.0.1"), &mut out).unwrap();
+    assert_eq!(out, br#"["US","CA"]"#);
+}
+
+#[test]
+fn visitor() {
+    // the paths of the strings, subdivisions skipped
+    #[derive(Default)]
+    struct Strings<'a> {
+        keys: Vec<&'a str>,
+        strings: Vec<String>,
+        maps: usize,
+    }
+
+    impl<'a> Visitor<'a> for Strings<'a> {
+        fn visit_map_start(&mut self, _len: usize) -> Result<Walk, Error> {
+            self.maps += 1;
+            Ok(Walk::Enter)
+        }
+
+        fn visit_key(&mut self, key: &'a str) -> Result<Walk, Error> {
+            self.keys.truncate(self.maps - 1);
+            self.keys.push(key);
+            Ok(match key {
+                "subdivisions" => Walk::Skip,
+                _ => Walk::Enter,
+            })
+        }
+
+        fn visit_map_end(&mut self) -> Result<(), Error> {
+            self.maps -= 1;
+            self.keys.truncate(self.maps);
+            Ok(())
+        }
+
+        fn visit_str(&mut self, value: &'a str) -> Result<(), Error> {
+            self.strings
+                .push(format!("{}={value}", self.keys.join(".")));
+            Ok(())
+        }
+    }
+
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let mut strings = Strings::default();
+    reader
+        .lookup_visit(IpAddr::from_str("2.125.160.216").unwrap(), &mut strings)
+        .unwrap();
+    assert_eq!(strings.maps, 0);
+    assert!(strings
+        .strings
+        .contains(&"city.names.en=Boxford".to_string()));
+    assert!(strings
+        .strings
+        .contains(&"location.time_zone=Europe/London".to_string()));
+    assert!(!strings.strings.iter().any(|s| s.contains("England")));
+
+    // the visitor decoding agrees with the decoded values
+    let languages = ["fr", "en"];
+    let options = [
+        FlattenOptions::default(),
+        FlattenOptions {
+            prefix: "geo",
+            index_format: IndexFormat::Brackets,
+            languages: Some(&languages),
+            include: &["*"],
+            exclude: &["*.geoname_id", "location.*"],
+        },
+    ];
+    for path in TREE_TEST_DATABASES {
+        let reader = Reader::open_file(path).unwrap();
+        for ip in sample_ips() {
+            let Ok(value) = reader.lookup::<Value>(ip) else {
+                continue;
+            };
+            for options in &options {
+                let expected = value
+                    .flatten(options)
+                    .map(|(path, value)| (path, value.clone()))
+                    .collect::<Vec<_>>();
+                let flat = reader
+                    .lookup_flatten(ip, options)
+                    .unwrap()
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                assert_eq!(flat, expected, "{path} {ip}");
+            }
+        }
+    }
+}
+
+#[test]
+fn schema() {
+    let reader = Reader::open_file("./testdata/Synthetic-Scalars-Test.mmdb").unwrap();
+    let schema = reader.infer_schema().unwrap();
+    assert_eq!((schema.networks, schema.records), (5, 5));
+    assert_eq!(
+        schema.root.types.keys().copied().collect::<Vec<_>>(),
+        ["array", "boolean", "map", "uint32", "utf8_string"]
+    );
+    let items = schema.root.items.as_ref().unwrap();
+    assert_eq!((items.count, items.networks), (2, 1));
+    assert_eq!(items.examples, [r#""US""#, r#""CA""#]);
+    let asn = &schema.root.fields["autonomous_system_number"];
+    assert_eq!(asn.types["uint32"], 1);
+    assert_eq!(asn.examples, ["13335"]);
+
+    // networks sharing a record are counted, the record walked once
+    let reader = Reader::open_file("./testdata/Synthetic-Decoder-Test.mmdb").unwrap();
+    let schema = reader.infer_schema().unwrap();
+    assert!(schema.networks > schema.records as u64);
+    for (key, field) in &schema.root.fields {
+        assert_eq!(field.networks, schema.networks, "{key}");
+    }
+    let map_x = &schema.root.fields["map"].fields["mapX"];
+    assert!(map_x.networks < schema.networks);
+    assert_eq!(
+        map_x.fields["arrayX"].items.as_ref().unwrap().types["uint32"],
+        3 * map_x.count
+    );
+
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let schema = reader.infer_schema().unwrap();
+    assert_eq!(schema.database_type, "GeoIP2-City");
+    let subdivisions = &schema.root.fields["subdivisions"];
+    assert_eq!(
+        subdivisions.types.keys().copied().collect::<Vec<_>>(),
+        ["array"]
+    );
+    let iso_code = &subdivisions.items.as_ref().unwrap().fields["iso_code"];
+    assert_eq!(
+        iso_code.types.keys().copied().collect::<Vec<_>>(),
+        ["utf8_string"]
+    );
+    assert!(iso_code.networks <= subdivisions.networks);
+
+    let text = schema.to_string();
+    assert!(text.starts_with("GeoIP2-City, "));
+    assert!(text.contains("\nsubdivisions[].iso_code "));
+
+    let mut json = Vec::new();
+    schema.write_json_schema(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.contains(r#""title": "GeoIP2-City""#));
+    assert!(json.contains(r#""required": ["#));
+}
+
+mod generated {
+    pub mod city {
+        include!("generated/city.rs");
+    }
+
+    pub mod decoder {
+        include!("generated/decoder.rs");
+    }
+}
+
+#[test]
+fn codegen() {
+    use generated::city::GeoIP2City;
+    use generated::decoder::SyntheticDecoderTest;
+
+    let reader = Reader::open_file("./testdata/GeoIP2-City-Test.mmdb").unwrap();
+    let schema = reader.infer_schema().unwrap();
+    let code = schema.to_rust("GeoIP2City");
+    assert_eq!(code, include_str!("generated/city.rs"));
+    // a schema printed and parsed back generates the same code
+    let parsed: maxminddb::schema::Schema = schema.to_string().parse().unwrap();
+    assert_eq!(parsed.database_type, "GeoIP2-City");
+    assert_eq!(parsed.to_rust("GeoIP2City"), code);
+
+    for ip in sample_ips() {
+        let Ok(city) = reader.lookup::<City>(ip) else {
+            continue;
+        };
+        let generated = reader.lookup::<GeoIP2City>(ip).unwrap();
+        assert_eq!(
+            generated.city.and_then(|city| city.geoname_id),
+            city.city.and_then(|city| city.geoname_id)
+        );
+        assert_eq!(
+            generated.country.and_then(|country| country.iso_code),
+            city.country.and_then(|country| country.iso_code)
+        );
+        assert_eq!(
+            generated.subdivisions.map(|s| s.len()),
+            city.subdivisions.map(|s| s.len())
+        );
+    }
+
+    let reader = Reader::open_file("./testdata/Synthetic-Decoder-Test.mmdb").unwrap();
+    let schema = reader.infer_schema().unwrap();
+    assert_eq!(
+        schema.to_rust("SyntheticDecoderTest"),
+        include_str!("generated/decoder.rs")
+    );
+    let record = reader
+        .lookup::<SyntheticDecoderTest>(IpAddr::from_str("1.1.1.0").unwrap())
+        .unwrap();
+    assert_eq!(record.array, Some(vec![1, 2, 3]));
+    assert_eq!(record.utf8_string, Some("unicode! ☯ - ♫"));
+    let map_x = record.map.unwrap().map_x.unwrap();
+    assert_eq!(map_x.array_x, Some(vec![7, 8, 9]));
+
+    // a hand written schema, the maps and arrays on the way implied
+    let schema: maxminddb::schema::Schema = "
+        # vendor.mmdb
+        id               uint32
+        place.names      map
+        place.names.en   utf8_string
+        tags[]           utf8_string
+        scores[]         uint16|uint32
+        mixed            int32|utf8_string
+        error.code       uint16
+        results[].vec.id uint32
+        option           map
+    "
+    .parse()
+    .unwrap();
+    let code = schema.to_rust("vendor");
+    assert!(code.contains("pub struct Vendor<'a> {\n"));
+    assert!(code.contains("    pub place: Option<Place<'a>>,\n"));
+    assert!(code.contains("    pub names: Option<maxminddb::models::Names<'a>>,\n"));
+    assert!(code.contains("    pub tags: Option<Vec<&'a str>>,\n"));
+    assert!(code.contains("    pub scores: Option<Vec<u32>>,\n"));
+    assert!(code.contains("    pub mixed: Option<maxminddb::Value<'a>>,\n"));
+    // names of the generated code are prefixed by their parent
+    assert!(code.contains("    pub error: Option<VendorError>,\n"));
+    assert!(code.contains("    pub results: Option<Vec<VendorResult>>,\n"));
+    assert!(code.contains("    pub vec: Option<ResultVec>,\n"));
+    assert!(code.contains("    pub option: Option<VendorOption>,\n"));
+    // maps without fields don't need a mutable binding
+    assert!(code.contains("        let vendor_option = VendorOption::default();\n"));
+    assert!(schema
+        .to_rust("error")
+        .contains("pub struct ErrorRecord<'a> {\n"));
+
+    assert!(matches!(
+        "a.b unknown".parse::<maxminddb::schema::Schema>(),
+        Err(Error::InvalidSchema(_))
+    ));
+}